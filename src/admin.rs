@@ -0,0 +1,193 @@
+use crate::converter::SharedConverter;
+use crate::error::OverridesError;
+use crate::idempotency::IdempotencyStore;
+use crate::ConversionResponse;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::{Request, Shutdown, State};
+
+/// Header carrying the admin token, which must match the configured `admin_token`.
+pub const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+/// Guard for admin routes, only succeeding when the request carries the configured admin token.
+/// Admin routes are refused when no `admin_token` is configured.
+pub struct AdminToken;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminToken {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let expected: Option<String> = request.rocket().figment().extract_inner("admin_token").ok();
+
+        match (expected, request.headers().get_one(ADMIN_TOKEN_HEADER)) {
+            (Some(expected), Some(token)) if expected == token => Outcome::Success(AdminToken),
+            _ => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+/// Gracefully shut the server down, only available with the `admin` feature.
+#[post("/admin/shutdown")]
+pub fn shutdown(_token: AdminToken, shutdown: Shutdown) -> Status {
+    shutdown.notify();
+    Status::Accepted
+}
+
+/// The outcome of a cache clearing.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct CacheCleared {
+    /// The number of responses removed from the cache.
+    pub evicted: usize,
+}
+
+/// Rebuild the conversion table, reading the overrides file again, and swap it in.
+/// The cached responses are cleared, they may have been computed with the previous rules.
+/// Only available with the `admin` feature.
+#[post("/admin/reload")]
+pub fn reload(
+    _token: AdminToken,
+    converter: &State<SharedConverter>,
+    store: &State<IdempotencyStore<ConversionResponse>>,
+) -> Result<Status, OverridesError> {
+    converter.reload()?;
+    store.clear();
+    Ok(Status::NoContent)
+}
+
+/// Clear the responses stored for idempotent conversions, the only ones the server caches.
+/// Only available with the `admin` feature.
+#[post("/admin/cache/clear")]
+pub fn clear_cache(
+    _token: AdminToken,
+    store: &State<IdempotencyStore<ConversionResponse>>,
+) -> Json<CacheCleared> {
+    Json(CacheCleared {
+        evicted: store.clear(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CacheCleared, ADMIN_TOKEN_HEADER};
+    use crate::idempotency::{IdempotencyStore, IDEMPOTENCY_KEY_HEADER};
+    use crate::rocket;
+    use crate::{ConversionRequest, ConversionResponse, ConversionResult, Unit};
+    use rocket::http::{Header, Status};
+    use rocket::local::asynchronous::Client;
+    use rocket::tokio::time::{timeout, Duration};
+    use speculoos::prelude::*;
+
+    async fn client() -> Client {
+        let figment = rocket::Config::figment().merge(("admin_token", "secret"));
+        Client::untracked(rocket().configure(figment))
+            .await
+            .expect("valid rocket instance")
+    }
+
+    #[rocket::async_test]
+    async fn should_shutdown_with_admin_token() {
+        let client = client().await;
+        let shutdown = client.rocket().shutdown();
+
+        let response = client
+            .post("/admin/shutdown")
+            .header(Header::new(ADMIN_TOKEN_HEADER, "secret"))
+            .dispatch()
+            .await;
+
+        assert_that!(response.status()).is_equal_to(Status::Accepted);
+        assert_that!(timeout(Duration::from_secs(1), shutdown).await).is_ok();
+    }
+
+    #[rocket::async_test]
+    async fn should_refuse_shutdown_without_admin_token() {
+        let client = client().await;
+        let shutdown = client.rocket().shutdown();
+
+        let response = client
+            .post("/admin/shutdown")
+            .header(Header::new(ADMIN_TOKEN_HEADER, "guess"))
+            .dispatch()
+            .await;
+
+        assert_that!(response.status()).is_equal_to(Status::Unauthorized);
+        assert_that!(timeout(Duration::from_millis(100), shutdown).await).is_err();
+    }
+
+    #[rocket::async_test]
+    async fn should_reload_overrides() {
+        let overrides =
+            |factor| format!("[[rules]]\nfrom = \"lb\"\nto = \"kilo\"\nfactor = {factor}");
+        let path = std::env::temp_dir().join(format!(
+            "conversion_api_{}_reload_overrides.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, overrides(0.5)).expect("writable overrides file");
+        let figment = rocket::Config::figment()
+            .merge(("admin_token", "secret"))
+            .merge(("overrides_file", &path));
+        let client = Client::untracked(rocket().configure(figment))
+            .await
+            .expect("valid rocket instance");
+        let convert = || async {
+            client
+                .post("/convert")
+                .json(&ConversionRequest::new(Unit::Lb, Unit::Kilo, 2.0))
+                .dispatch()
+                .await
+                .into_json::<ConversionResponse>()
+                .await
+                .map(|response| response.result)
+        };
+
+        let before = convert().await;
+        std::fs::write(&path, overrides(0.25)).expect("writable overrides file");
+        let response = client
+            .post("/admin/reload")
+            .header(Header::new(ADMIN_TOKEN_HEADER, "secret"))
+            .dispatch()
+            .await;
+        let after = convert().await;
+
+        assert_that!(before).contains(ConversionResult::Number(1.0));
+        assert_that!(response.status()).is_equal_to(Status::NoContent);
+        assert_that!(after).contains(ConversionResult::Number(0.5));
+    }
+
+    #[rocket::async_test]
+    async fn should_clear_cached_responses() {
+        let client = client().await;
+        for key in ["first", "second"] {
+            let response = client
+                .post("/convert")
+                .header(Header::new(IDEMPOTENCY_KEY_HEADER, key))
+                .json(&ConversionRequest::new(Unit::Lb, Unit::Kilo, 2.0))
+                .dispatch()
+                .await;
+            assert_that!(response.status()).is_equal_to(Status::Ok);
+        }
+        let store = client
+            .rocket()
+            .state::<IdempotencyStore<ConversionResponse>>()
+            .expect("managed idempotency store");
+        assert_that!(store.len()).is_equal_to(2);
+
+        let clear = || async {
+            client
+                .post("/admin/cache/clear")
+                .header(Header::new(ADMIN_TOKEN_HEADER, "secret"))
+                .dispatch()
+                .await
+                .into_json::<CacheCleared>()
+                .await
+        };
+
+        assert_that!(clear().await).contains(CacheCleared { evicted: 2 });
+        assert_that!(store.len()).is_equal_to(0);
+        assert_that!(clear().await).contains(CacheCleared { evicted: 0 });
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{units, Unit};
+use rocket::serde::json::serde_json::{self, Value};
+use std::collections::HashMap;
+
+/// Replace the `from`, `to` and `through` units of a JSON request designated by one of
+/// the configured `aliases` with the unit they stand for. Built-in names always take precedence
+/// over aliases, they are left as is.
+pub fn resolve(request: &mut Value, aliases: &HashMap<String, Unit>) {
+    let Some(fields) = request.as_object_mut() else {
+        return;
+    };
+
+    for field in ["from", "to", "through"] {
+        let unit = match fields.get(field) {
+            Some(Value::String(name)) if units::parse(name).is_err() => aliases.get(name).copied(),
+            _ => None,
+        };
+        if let Some(unit) = unit {
+            fields.insert(field.to_string(), serde_json::json!(unit));
+        }
+    }
+}
+
+/// Resolve the aliases of a JSON request body like [`resolve`] does, `None` when the body
+/// is not valid JSON or has no unit designated by an alias.
+pub fn resolve_body(body: &str, aliases: &HashMap<String, Unit>) -> Option<String> {
+    let mut request: Value = serde_json::from_str(body).ok()?;
+    let original = request.clone();
+    resolve(&mut request, aliases);
+
+    (request != original).then(|| request.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::alias::resolve_body;
+    use crate::{rocket, Unit};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn should_resolve_aliases_of_a_body() {
+        let aliases = HashMap::from([
+            ("kilogramme".to_string(), Unit::Kilo),
+            ("lb".to_string(), Unit::Gram),
+        ]);
+
+        assert_that!(resolve_body(
+            r#"{"from":"kilogramme","to":"lb","quantity":1}"#,
+            &aliases
+        ))
+        .is_some()
+        .is_equal_to(r#"{"from":"kilo","quantity":1,"to":"lb"}"#.to_string());
+        assert_that!(resolve_body(
+            r#"{"from":"kilogrammes","to":"lb"}"#,
+            &aliases
+        ))
+        .is_none();
+    }
+
+    #[test]
+    fn should_convert_configured_alias() {
+        let figment =
+            rocket::Config::figment().merge(("aliases", HashMap::from([("pound", "lb")])));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"pound","to":"gram","quantity":1}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_f64()).contains(453.59237);
+    }
+
+    #[test]
+    fn should_not_share_aliases_between_instances() {
+        let figment =
+            rocket::Config::figment().merge(("aliases", HashMap::from([("livre", "lb")])));
+        let _configured =
+            Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"livre","to":"gram","quantity":1}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+    }
+}
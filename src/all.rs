@@ -0,0 +1,168 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::converter::Converter;
+use crate::difference::Measure;
+use crate::error::ConvertError;
+use crate::format::Decimals;
+use crate::{system, ConversionRequest, Unit};
+use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
+use rocket::State;
+use std::collections::BTreeMap;
+
+/// Requested precisions past this number of decimal digits are lowered to it, an `f64`
+/// holding no more significant digits.
+const MAX_PRECISION: usize = 17;
+
+/// A quantity to convert, to the given target units or to every other unit of its dimension.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct AllConversionRequest {
+    #[serde(flatten)]
+    measure: Measure,
+    #[serde(default)]
+    targets: Option<Vec<Target>>,
+}
+
+/// A target unit, along with the number of decimal digits its result is rounded to.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde", untagged)]
+enum Target {
+    WithPrecision {
+        #[serde(deserialize_with = "system::unit_or_qualified")]
+        unit: Unit,
+        precision: usize,
+    },
+    Unit(#[serde(deserialize_with = "system::unit_or_qualified")] Unit),
+}
+
+/// Convert a quantity to every other unit of its dimension, returning the results keyed
+/// by target unit. Disabled conversions are left out.
+///
+/// When `targets` are given, the quantity is only converted to them, a target given as
+/// `{"unit": "lb", "precision": 2}` being rounded to that many decimal digits rather than
+/// the configured precision. Converting to a disabled target then fails.
+#[post("/convert/all", data = "<conversion>")]
+pub fn convert_all(
+    conversion: Json<AllConversionRequest>,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<BTreeMap<Unit, f64>>, ConvertError<'static>> {
+    let AllConversionRequest { measure, targets } = conversion.into_inner();
+    let Measure { value, unit } = measure;
+
+    let mut results = BTreeMap::new();
+    if let Some(targets) = targets {
+        for target in targets {
+            let (to, precision) = match target {
+                Target::WithPrecision { unit, precision } => (unit, Some(precision)),
+                Target::Unit(unit) => (unit, None),
+            };
+            let request = ConversionRequest::new(unit, to, value);
+            let result = match precision {
+                Some(precision) => {
+                    let rounding = Decimals(precision.min(MAX_PRECISION));
+                    request.execute_with_rounding(&converter, &rounding)?.0
+                }
+                None => request.execute(&converter)?,
+            };
+            results.insert(to, result);
+        }
+
+        quotas.consume(&api_key, results.len() as u64)?;
+        return Ok(Json(results));
+    }
+
+    for to in Unit::ALL {
+        if to == unit || to.dimension() != unit.dimension() {
+            continue;
+        }
+
+        match ConversionRequest::new(unit, to, value).execute(&converter) {
+            Ok(result) => {
+                results.insert(to, result);
+            }
+            Err(ConvertError::ConversionDisabled { .. }) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    quotas.consume(&api_key, results.len() as u64)?;
+    Ok(Json(results))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::UnitPair;
+    use crate::{rocket, Unit};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn should_convert_to_every_unit_of_the_dimension() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/all")
+            .json(&json!({"value": 1, "unit": "kg"}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let results: BTreeMap<Unit, f64> = response.into_json().expect("valid json body");
+        let mass_units = Unit::ALL
+            .iter()
+            .filter(|unit| unit.dimension() == Unit::Kilo.dimension())
+            .count();
+        assert_that!(results.len()).is_equal_to(mass_units - 1);
+        assert_that!(results.get(&Unit::Gram)).contains(&1000.0);
+        assert_that!(results.get(&Unit::Lb)).contains(&2.20462262);
+        assert_that!(results.get(&Unit::Kilo)).is_none();
+    }
+
+    #[test]
+    fn should_leave_out_disabled_conversions() {
+        let disabled = vec![UnitPair {
+            from: Unit::Kilo,
+            to: Unit::Lb,
+        }];
+        let figment = rocket::Config::figment().merge(("disabled_conversions", disabled));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/all")
+            .json(&json!({"value": 1, "unit": "kilo"}))
+            .dispatch();
+
+        let results: BTreeMap<Unit, f64> = response.into_json().expect("valid json body");
+        assert_that!(results.get(&Unit::Lb)).is_none();
+        assert_that!(results.get(&Unit::Gram)).contains(&1000.0);
+    }
+
+    #[test]
+    fn should_round_each_target_to_its_precision() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/all")
+            .json(&json!({
+                "value": 1.23456,
+                "unit": "kg",
+                "targets": [
+                    {"unit": "lb", "precision": 2},
+                    {"unit": "gram", "precision": 0},
+                    {"unit": "ton", "system": "short"},
+                ]
+            }))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let results: BTreeMap<Unit, f64> = response.into_json().expect("valid json body");
+        assert_that!(results.len()).is_equal_to(3);
+        assert_that!(results.get(&Unit::Lb)).contains(&2.72);
+        assert_that!(results.get(&Unit::Gram)).contains(&1235.0);
+        assert_that!(results.get(&Unit::ShortTon)).contains(&0.00136087);
+    }
+}
@@ -0,0 +1,326 @@
+use crate::error::ConvertError;
+use rocket::fairing::AdHoc;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, PoisonError};
+use time::{Month, OffsetDateTime};
+
+/// Header carrying the API key, which must be one of the configured `api_keys`.
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Manage the [`ApiKeys`] read from the configured `api_keys`, and the [`Quotas`] of
+/// the configured `api_quotas`.
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("API keys", |rocket| async {
+        let keys: Vec<String> = rocket
+            .figment()
+            .extract_inner("api_keys")
+            .unwrap_or_default();
+        let quotas = rocket
+            .figment()
+            .extract_inner("api_quotas")
+            .unwrap_or_default();
+
+        rocket
+            .manage(ApiKeys(keys.into_iter().collect()))
+            .manage(Quotas::new(quotas))
+    })
+}
+
+/// The API keys accepted by the routes guarded by [`ApiKey`].
+pub struct ApiKeys(HashSet<String>);
+
+/// Guard for the conversion routes, only succeeding when the request carries one of the configured
+/// API keys. Every request is accepted when no `api_keys` are configured.
+pub struct ApiKey(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiKey {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let keys = match request.rocket().state::<ApiKeys>() {
+            Some(ApiKeys(keys)) if !keys.is_empty() => keys,
+            _ => return Outcome::Success(ApiKey(None)),
+        };
+
+        match request.headers().get_one(API_KEY_HEADER) {
+            Some(key) if keys.contains(key) => Outcome::Success(ApiKey(Some(key.to_string()))),
+            _ => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+/// A calendar month, in UTC.
+type Period = (i32, Month);
+
+fn current_period() -> Period {
+    let now = OffsetDateTime::now_utc();
+    (now.year(), now.month())
+}
+
+/// The number of conversions each API key can execute per calendar month.
+/// Keys without a quota are unlimited.
+pub struct Quotas {
+    limits: HashMap<String, u64>,
+    usage: Mutex<HashMap<String, (Period, u64)>>,
+}
+
+impl Quotas {
+    pub fn new(limits: HashMap<String, u64>) -> Self {
+        Quotas {
+            limits,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count `conversions` successful conversions against the quota of `key`, refused when
+    /// they exceed what is left of the quota of the current month.
+    pub fn consume(&self, key: &ApiKey, conversions: u64) -> Result<(), ConvertError<'static>> {
+        let Some((key, limit)) = key
+            .0
+            .as_ref()
+            .and_then(|key| Some((key, *self.limits.get(key)?)))
+        else {
+            return Ok(());
+        };
+
+        let period = current_period();
+        let mut usage = self.usage.lock().unwrap_or_else(PoisonError::into_inner);
+        let (used_period, used) = usage.entry(key.clone()).or_insert((period, 0));
+        if *used_period != period {
+            *used_period = period;
+            *used = 0;
+        }
+
+        if used.saturating_add(conversions) > limit {
+            return Err(ConvertError::QuotaExceeded { limit });
+        }
+
+        *used += conversions;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::API_KEY_HEADER;
+    use crate::error::ErrorBody;
+    use crate::idempotency::IDEMPOTENCY_KEY_HEADER;
+    use crate::rocket;
+    use rocket::http::{ContentType, Header, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+    use std::collections::HashMap;
+
+    fn client() -> Client {
+        let figment = rocket::Config::figment().merge(("api_keys", ["paid", "other"]));
+        Client::untracked(rocket().configure(figment)).expect("valid rocket instance")
+    }
+
+    fn convert(client: &Client, key: Option<&'static str>) -> Status {
+        let mut request = client
+            .post("/convert")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"kilo","to":"gram","quantity":1}"#);
+        if let Some(key) = key {
+            request = request.header(Header::new(API_KEY_HEADER, key));
+        }
+        request.dispatch().status()
+    }
+
+    #[test]
+    fn should_convert_with_valid_api_key() {
+        assert_that!(convert(&client(), Some("paid"))).is_equal_to(Status::Ok);
+    }
+
+    #[test]
+    fn should_refuse_conversion_without_valid_api_key() {
+        let client = client();
+
+        assert_that!(convert(&client, None)).is_equal_to(Status::Unauthorized);
+        assert_that!(convert(&client, Some("guess"))).is_equal_to(Status::Unauthorized);
+    }
+
+    #[test]
+    fn should_refuse_every_conversion_route_without_api_key() {
+        let client = client();
+        let conversion = r#"{"from":"kilo","to":"gram","quantity":1}"#;
+        let routes = [
+            ("/convert/batch", format!("[{conversion}]")),
+            ("/convert/stream", format!("[{conversion}]")),
+            (
+                "/convert/array",
+                r#"{"from":"kilo","to":"gram","quantities":[1]}"#.to_string(),
+            ),
+            (
+                "/convert/sweep",
+                r#"{"from":"kilo","to":"gram","start":0,"end":1,"step":1}"#.to_string(),
+            ),
+            ("/convert/all", r#"{"value":1,"unit":"kilo"}"#.to_string()),
+            (
+                "/convert/mixed",
+                r#"{"quantity":"1 lb 4 oz","to":"gram"}"#.to_string(),
+            ),
+            (
+                "/convert/rate",
+                r#"{"from":"kg/h","to":"g/min","quantity":1}"#.to_string(),
+            ),
+            (
+                "/convert/difference",
+                r#"{"a":{"value":1,"unit":"kg"},"b":{"value":1,"unit":"lb"}}"#.to_string(),
+            ),
+            (
+                "/convert/assert",
+                r#"{"from":"kilo","to":"gram","quantity":1,"expected":1000}"#.to_string(),
+            ),
+            ("/sessions/unknown/convert", r#"{"quantity":1}"#.to_string()),
+        ];
+
+        for (path, body) in routes {
+            let post = |key: Option<&'static str>| {
+                let mut request = client.post(path).header(ContentType::JSON).body(&body);
+                if let Some(key) = key {
+                    request = request.header(Header::new(API_KEY_HEADER, key));
+                }
+                request.dispatch().status()
+            };
+
+            asserting!(path)
+                .that(&post(None))
+                .is_equal_to(Status::Unauthorized);
+            asserting!(path)
+                .that(&post(Some("paid")))
+                .is_not_equal_to(Status::Unauthorized);
+        }
+
+        let legacy = |key: Option<&'static str>| {
+            let mut request = client.get("/convert.json?from=kilo&to=gram&quantity=1");
+            if let Some(key) = key {
+                request = request.header(Header::new(API_KEY_HEADER, key));
+            }
+            request.dispatch().status()
+        };
+        assert_that!(legacy(None)).is_equal_to(Status::Unauthorized);
+        assert_that!(legacy(Some("paid"))).is_equal_to(Status::PermanentRedirect);
+    }
+
+    #[test]
+    fn should_keep_public_endpoints_open() {
+        let client = client();
+
+        for path in ["/ready", "/dimensions", "/units/reachable?from=kilo"] {
+            let response = client.get(path).dispatch();
+
+            asserting!(path)
+                .that(&response.status())
+                .is_equal_to(Status::Ok);
+        }
+    }
+
+    #[test]
+    fn should_refuse_conversions_beyond_the_quota() {
+        let figment = rocket::Config::figment()
+            .merge(("api_keys", ["paid", "other"]))
+            .merge(("api_quotas", HashMap::from([("paid", 2)])));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        assert_that!(convert(&client, Some("paid"))).is_equal_to(Status::Ok);
+        assert_that!(convert(&client, Some("paid"))).is_equal_to(Status::Ok);
+
+        let response = client
+            .post("/convert")
+            .header(ContentType::JSON)
+            .header(Header::new(API_KEY_HEADER, "paid"))
+            .body(r#"{"from":"kilo","to":"gram","quantity":1}"#)
+            .dispatch();
+        assert_that!(response.status()).is_equal_to(Status::TooManyRequests);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error).contains("quota of 2 conversions");
+
+        // Keys without a quota are unlimited
+        assert_that!(convert(&client, Some("other"))).is_equal_to(Status::Ok);
+    }
+
+    fn client_with_quota(quota: u64) -> Client {
+        let figment = rocket::Config::figment()
+            .merge(("api_keys", ["paid"]))
+            .merge(("api_quotas", HashMap::from([("paid", quota)])));
+        Client::untracked(rocket().configure(figment)).expect("valid rocket instance")
+    }
+
+    #[test]
+    fn should_charge_every_conversion_of_a_request() {
+        let client = client_with_quota(3);
+        let post = |path: &'static str, body: &str| {
+            client
+                .post(path)
+                .header(ContentType::JSON)
+                .header(Header::new(API_KEY_HEADER, "paid"))
+                .body(body)
+                .dispatch()
+                .status()
+        };
+        let array = |quantities: &str| {
+            post(
+                "/convert/array",
+                &format!(r#"{{"from":"kilo","to":"gram","quantities":[{quantities}]}}"#),
+            )
+        };
+
+        assert_that!(array("1,2,3,4")).is_equal_to(Status::TooManyRequests);
+        assert_that!(array("1,2")).is_equal_to(Status::Ok);
+        let conversion = r#"{"from":"kilo","to":"gram","quantity":1}"#;
+        assert_that!(post(
+            "/convert/batch",
+            &format!("[{conversion},{conversion}]")
+        ))
+        .is_equal_to(Status::TooManyRequests);
+        assert_that!(convert(&client, Some("paid"))).is_equal_to(Status::Ok);
+        assert_that!(convert(&client, Some("paid"))).is_equal_to(Status::TooManyRequests);
+    }
+
+    #[test]
+    fn should_not_charge_failed_or_replayed_conversions() {
+        let client = client_with_quota(1);
+        let post = |body: &'static str| {
+            client
+                .post("/convert")
+                .header(ContentType::JSON)
+                .header(Header::new(API_KEY_HEADER, "paid"))
+                .header(Header::new(IDEMPOTENCY_KEY_HEADER, "same"))
+                .body(body)
+                .dispatch()
+                .status()
+        };
+
+        assert_that!(post(r#"{"from":"kilo","to":"degree","quantity":1}"#))
+            .is_equal_to(Status::BadRequest);
+        assert_that!(post(r#"{"from":"kilo","to":"gram","quantity":1}"#)).is_equal_to(Status::Ok);
+        assert_that!(post(r#"{"from":"kilo","to":"gram","quantity":1}"#)).is_equal_to(Status::Ok);
+        assert_that!(convert(&client, Some("paid"))).is_equal_to(Status::TooManyRequests);
+    }
+
+    #[test]
+    fn should_end_the_stream_once_the_quota_is_depleted() {
+        let client = client_with_quota(1);
+        let conversion = r#"{"from":"kilo","to":"gram","quantity":1}"#;
+
+        let response = client
+            .post("/convert/stream")
+            .header(ContentType::JSON)
+            .header(Header::new(API_KEY_HEADER, "paid"))
+            .body(format!("[{conversion},{conversion}]"))
+            .dispatch();
+
+        let entries: Vec<Value> = response.into_json().expect("valid json array");
+        assert_that!(entries).has_length(2);
+        assert_that!(entries[0]["result"].as_f64()).contains(1000.0);
+        assert_that!(entries[1]["error"].as_str())
+            .is_some()
+            .contains("quota of 1 conversions");
+    }
+}
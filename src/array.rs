@@ -0,0 +1,238 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::config::SubnormalPolicy;
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::format::{self, Decimals, RoundingStrategy};
+use crate::{system, Unit};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::State;
+use std::num::FpCategory;
+
+/// Convert many quantities between the same two units.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct ArrayConversionRequest {
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub from: Unit,
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub to: Unit,
+    pub quantities: Vec<f64>,
+}
+
+/// The converted quantities, in the order they were requested.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct ArrayConversion {
+    #[serde(serialize_with = "format::serialize_numbers")]
+    pub results: Vec<f64>,
+}
+
+/// Convert every quantity with the same conversion rule, looked up once for the whole array
+/// and applied with SIMD instructions. Results are handled like `POST /convert` results:
+/// subnormal ones follow the configured policy, they are rounded then clamped, although
+/// clamped results are not flagged. Arrays of more than the configured `max_batch_size` quantities are rejected.
+#[post("/convert/array", data = "<conversion>")]
+pub fn convert_array(
+    conversion: Json<ArrayConversionRequest>,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<ArrayConversion>, ConvertError<'static>> {
+    let ArrayConversionRequest {
+        from,
+        to,
+        quantities,
+    } = conversion.into_inner();
+
+    let max = converter.config.max_batch_size;
+    if quantities.len() > max {
+        return Err(ConvertError::BatchTooLarge {
+            max,
+            got: quantities.len(),
+        });
+    }
+
+    let results = convert_quantities(from, to, quantities, &converter)?;
+    quotas.consume(&api_key, results.len() as u64)?;
+    Ok(Json(ArrayConversion { results }))
+}
+
+/// Convert every quantity in place with the rule converting `from` to `to`, applying the
+/// configured subnormal policy, then rounding the results to the configured precision of
+/// the target dimension and clamping them to the configured bounds.
+pub(crate) fn convert_quantities(
+    from: Unit,
+    to: Unit,
+    mut quantities: Vec<f64>,
+    converter: &Converter,
+) -> Result<Vec<f64>, ConvertError<'static>> {
+    let rule = from.rule_to(to, converter)?;
+    let rounding = Decimals(converter.config.decimals(to.dimension()));
+
+    rule.convert_slice(&mut quantities);
+
+    for (index, result) in quantities.iter_mut().enumerate() {
+        if !result.is_finite() {
+            return Err(ConvertError::NonFiniteResultAt { from, to, index });
+        }
+
+        let exact = match (result.classify(), converter.config.subnormal_results) {
+            (FpCategory::Subnormal, SubnormalPolicy::Flush) => {
+                warn!(
+                    "Flushing the subnormal result {} at index {} of {:?} to {:?} to zero",
+                    result, index, from, to
+                );
+                0.0_f64.copysign(*result)
+            }
+            (FpCategory::Subnormal, SubnormalPolicy::Reject) => {
+                return Err(ConvertError::SubnormalResultAt { from, to, index });
+            }
+            _ => *result,
+        };
+        (*result, _) = converter.config.clamp(rounding.round(exact));
+    }
+
+    Ok(quantities)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::array::ArrayConversion;
+    use crate::conversion::ConversionRule;
+    use crate::error::ErrorBody;
+    use crate::rocket;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::{self, json};
+    use speculoos::prelude::*;
+    use std::hint::black_box;
+    use std::time::Instant;
+
+    #[test]
+    fn should_convert_every_quantity() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/array")
+            .json(&json!({"from": "kg", "to": "g", "quantities": [1, 2.5, -3, 0, 0.001]}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let conversion: ArrayConversion = response.into_json().expect("valid json body");
+        assert_that!(conversion.results).is_equal_to(vec![1000.0, 2500.0, -3000.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn should_refuse_non_finite_results() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/array")
+            .json(&json!({"from": "mpg", "to": "l/100km", "quantities": [30, 0]}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::BadRequest);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error).contains("at index 1");
+    }
+
+    #[test]
+    fn should_apply_the_subnormal_policy_to_every_quantity() {
+        let convert = |policy: &str| {
+            let figment = rocket::Config::figment().merge(("subnormal_results", policy));
+            let client =
+                Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+            let response = client
+                .post("/convert/array")
+                .json(&json!({"from": "g", "to": "metric ton", "quantities": [1, -1e-303]}))
+                .dispatch();
+            (
+                response.status(),
+                response.into_string().unwrap_or_default(),
+            )
+        };
+
+        let (status, flushed) = convert("flush");
+        assert_that!(status).is_equal_to(Status::Ok);
+        let flushed: ArrayConversion = serde_json::from_str(&flushed).expect("valid json body");
+        assert_that!(flushed.results[1] == 0.0 && flushed.results[1].is_sign_negative()).is_true();
+
+        let (status, rejected) = convert("reject");
+        assert_that!(status).is_equal_to(Status::UnprocessableEntity);
+        let body: ErrorBody = serde_json::from_str(&rejected).expect("valid json body");
+        assert_that!(body.error).contains("at index 1");
+    }
+
+    #[test]
+    fn should_clamp_every_quantity() {
+        let figment = rocket::Config::figment()
+            .merge(("min_result", 0))
+            .merge(("max_result", 2000));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/array")
+            .json(&json!({"from": "kg", "to": "g", "quantities": [1, -1, 3]}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let conversion: ArrayConversion = response.into_json().expect("valid json body");
+        assert_that!(conversion.results).is_equal_to(vec![1000.0, 0.0, 2000.0]);
+    }
+
+    #[test]
+    fn should_refuse_arrays_beyond_the_batch_size() {
+        let figment = rocket::Config::figment().merge(("max_batch_size", 3));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let convert = |quantities: &[f64]| {
+            client
+                .post("/convert/array")
+                .json(&json!({"from": "kg", "to": "g", "quantities": quantities}))
+                .dispatch()
+                .status()
+        };
+
+        assert_that!(convert(&[1.0, 2.0, 3.0])).is_equal_to(Status::Ok);
+        assert_that!(convert(&[1.0, 2.0, 3.0, 4.0])).is_equal_to(Status::BadRequest);
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with `cargo test --release -- --ignored --nocapture`"]
+    fn should_time_simd_conversion_of_a_million_quantities() {
+        let rule = ConversionRule::try_from(&["kg", "lb", "2.20462262"]).expect("valid rule");
+        let quantities: Vec<f64> = (0..1_000_000).map(|i| f64::from(i) * 0.001).collect();
+
+        // The fastest of several runs, converting a fresh copy of the quantities in place
+        let time = |convert: &dyn Fn(&mut [f64])| {
+            let mut converted = quantities.clone();
+            let fastest = (0..10)
+                .map(|_| {
+                    converted.copy_from_slice(&quantities);
+                    let start = Instant::now();
+                    convert(black_box(&mut converted));
+                    start.elapsed()
+                })
+                .min()
+                .unwrap_or_default();
+            (converted, fastest)
+        };
+
+        let (scalar, scalar_time) = time(&|quantities| {
+            for quantity in quantities {
+                *quantity = rule.convert(*quantity);
+            }
+        });
+        let (simd, simd_time) = time(&|quantities| rule.convert_slice(quantities));
+
+        println!(
+            "1M quantities: scalar {scalar_time:?}, SIMD {simd_time:?}, speedup {:.2}x",
+            scalar_time.as_secs_f64() / simd_time.as_secs_f64()
+        );
+        assert_that!(simd
+            .iter()
+            .zip(&scalar)
+            .all(|(simd, scalar)| simd.to_bits() == scalar.to_bits()))
+        .is_true();
+    }
+}
@@ -0,0 +1,113 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::{numeric, ConversionRequest};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::State;
+
+/// A conversion and the result it is expected to give.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct AssertionRequest {
+    #[serde(flatten)]
+    pub conversion: ConversionRequest,
+    #[serde(deserialize_with = "numeric::number_or_string")]
+    pub expected: f64,
+    /// The largest absolute difference accepted between the result and `expected`.
+    #[serde(default)]
+    pub tolerance: f64,
+}
+
+/// Whether the conversion gave the expected result, and what it actually gave otherwise.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct AssertionOutcome {
+    pub pass: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<f64>,
+    /// `actual - expected`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difference: Option<f64>,
+}
+
+/// Execute the conversion and compare its result, truncated after the 8th decimal digit,
+/// to the expected one.
+#[post("/convert/assert", data = "<assertion>")]
+pub fn convert_assert(
+    assertion: Json<AssertionRequest>,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<AssertionOutcome>, ConvertError<'static>> {
+    let actual = assertion.conversion.execute(&converter)?;
+    quotas.consume(&api_key, 1)?;
+    let difference = actual - assertion.expected;
+
+    let outcome = if difference.abs() <= assertion.tolerance {
+        AssertionOutcome {
+            pass: true,
+            actual: None,
+            expected: None,
+            difference: None,
+        }
+    } else {
+        AssertionOutcome {
+            pass: false,
+            actual: Some(actual),
+            expected: Some(assertion.expected),
+            difference: Some(difference),
+        }
+    };
+
+    Ok(Json(outcome))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::assertion::AssertionOutcome;
+    use crate::rocket;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+
+    fn assert_conversion(expected: f64, tolerance: f64) -> AssertionOutcome {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post("/convert/assert")
+            .json(&json!({
+                "from": "kg",
+                "to": "g",
+                "quantity": 1,
+                "expected": expected,
+                "tolerance": tolerance,
+            }))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        response.into_json().expect("valid json body")
+    }
+
+    #[test]
+    fn should_pass_within_tolerance() {
+        assert_that!(assert_conversion(1000.001, 0.01)).is_equal_to(AssertionOutcome {
+            pass: true,
+            actual: None,
+            expected: None,
+            difference: None,
+        });
+    }
+
+    #[test]
+    fn should_fail_beyond_tolerance() {
+        assert_that!(assert_conversion(999.0, 0.5)).is_equal_to(AssertionOutcome {
+            pass: false,
+            actual: Some(1000.0),
+            expected: Some(999.0),
+            difference: Some(1.0),
+        });
+    }
+}
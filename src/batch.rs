@@ -0,0 +1,263 @@
+use crate::alias;
+use crate::api_key::{ApiKey, Quotas};
+use crate::conversion::ConversionRule;
+use crate::converter::Converter;
+use crate::error::{ConvertError, ErrorBody};
+use crate::format::OutputOptions;
+use crate::{ConversionRequest, ConversionResponse, ConversionResult, Unit};
+use rocket::serde::json::{serde_json, Json, Value};
+use rocket::serde::{Deserialize, Serialize};
+use rocket::tokio::task;
+use rocket::State;
+use std::collections::HashMap;
+
+/// The outcome of a single batch element, either `{"result": ...}` or `{"error": ...}`.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde", untagged)]
+pub enum BatchEntry {
+    Converted(ConversionResponse),
+    Failed(ErrorBody),
+}
+
+impl From<Result<ConversionResponse, ErrorBody>> for BatchEntry {
+    fn from(result: Result<ConversionResponse, ErrorBody>) -> Self {
+        match result {
+            Ok(response) => BatchEntry::Converted(response),
+            Err(error) => BatchEntry::Failed(error),
+        }
+    }
+}
+
+/// Convert every request of the batch, returning an entry per request in the same order.
+///
+/// The rule of each pair of units is resolved once for the whole batch, then applied to
+/// every element converting between them. Conversions run on the blocking thread pool so
+/// a large batch does not starve the async runtime. Each element is parsed and converted on
+/// its own: an invalid element is reported as an error entry without failing the rest of
+/// the batch. Only batches holding more than the configured `max_batch_size` conversions are
+/// rejected as a whole.
+#[post("/convert/batch?<options..>", data = "<batch>")]
+pub async fn convert_batch(
+    batch: Json<Vec<Value>>,
+    options: OutputOptions,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<Vec<BatchEntry>>, ConvertError<'static>> {
+    let batch = batch.into_inner();
+    let max = converter.config.max_batch_size;
+    if batch.len() > max {
+        return Err(ConvertError::BatchTooLarge {
+            max,
+            got: batch.len(),
+        });
+    }
+
+    let entries: Vec<BatchEntry> = task::spawn_blocking(move || {
+        let mut rules = HashMap::new();
+        batch
+            .into_iter()
+            .map(|element| convert_element(element, &mut rules, &options, &converter).into())
+            .collect()
+    })
+    .await
+    .map_err(|err| ConvertError::Internal(err.to_string()))?;
+
+    let converted = entries
+        .iter()
+        .filter(|entry| matches!(entry, BatchEntry::Converted(_)))
+        .count();
+    quotas.consume(&api_key, converted as u64)?;
+    Ok(Json(entries))
+}
+
+/// A rule resolved for the batch, or the result a dimension mismatch is coerced to by the
+/// `on_mismatch` policy.
+#[derive(Clone)]
+enum Resolved {
+    Rule(ConversionRule),
+    Coerced(ConversionResult),
+}
+
+/// The rules resolved for the batch, keyed by their `from`, `to` and `through` units.
+type Rules = HashMap<(Unit, Unit, Option<Unit>), Result<Resolved, ErrorBody>>;
+
+fn convert_element(
+    mut element: Value,
+    rules: &mut Rules,
+    options: &OutputOptions,
+    converter: &Converter,
+) -> Result<ConversionResponse, ErrorBody> {
+    alias::resolve(&mut element, &converter.config.aliases);
+    let request: ConversionRequest = serde_json::from_value(element).map_err(|err| ErrorBody {
+        error: err.to_string(),
+        retryable: false,
+    })?;
+
+    let rule = rules
+        .entry((request.from, request.to, request.through))
+        .or_insert_with(|| match request.rule(converter) {
+            Ok(rule) => Ok(Resolved::Rule(rule)),
+            Err(err @ ConvertError::DimensionMismatch { .. }) => options
+                .on_mismatch
+                .coerced()
+                .map(Resolved::Coerced)
+                .ok_or_else(|| ErrorBody::from(&err)),
+            Err(err) => Err(ErrorBody::from(&err)),
+        })
+        .clone()?;
+    let rule = match rule {
+        Resolved::Rule(rule) => rule,
+        Resolved::Coerced(result) => return Ok(ConversionResponse::plain(result)),
+    };
+
+    ConversionResponse::with_rule(&request, None, rule, options, converter)
+        .map_err(|err| ErrorBody::from(&err))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::batch::BatchEntry;
+    use crate::error::ErrorBody;
+    use crate::{rocket, ConversionRequest, ConversionResponse, ConversionResult, Unit};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn should_convert_large_batch() {
+        let figment = rocket::Config::figment().merge(("max_batch_size", 10_000));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let batch: Vec<ConversionRequest> = (0..10_000)
+            .map(|quantity| ConversionRequest::new(Unit::Kilo, Unit::Gram, quantity as f64))
+            .collect();
+
+        let response = client.post("/convert/batch").json(&batch).dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let results: Vec<BatchEntry> = response.into_json().expect("valid json array");
+        assert_that!(results).has_length(10_000);
+        for (quantity, entry) in results.into_iter().enumerate() {
+            assert_that!(entry).is_equal_to(BatchEntry::Converted(ConversionResponse::plain(
+                ConversionResult::Number(quantity as f64 * 1000.0),
+            )));
+        }
+    }
+
+    #[test]
+    fn should_report_failures_per_element() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let batch = json!([
+            {"from": "kilo", "to": "gram", "quantity": 1},
+            {"from": "kilo", "to": "degree", "quantity": 1},
+            {"from": "parsec", "to": "gram", "quantity": 1},
+            {"from": "gram", "to": "kilo", "quantity": 1000},
+        ]);
+
+        let response = client.post("/convert/batch").json(&batch).dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let results: Vec<BatchEntry> = response.into_json().expect("valid json array");
+        let converted = |result| {
+            BatchEntry::Converted(ConversionResponse::plain(ConversionResult::Number(result)))
+        };
+        assert_that!(results).has_length(4);
+        assert_that!(results[0]).is_equal_to(converted(1000.0));
+        assert_that!(results[1]).is_equal_to(BatchEntry::Failed(ErrorBody {
+            error: "Cannot convert Kilo to Degree, they do not measure the same dimension"
+                .to_string(),
+            retryable: false,
+        }));
+        assert_that!(
+            matches!(&results[2], BatchEntry::Failed(body) if body.error.contains("parsec"))
+        )
+        .is_true();
+        assert_that!(results[3]).is_equal_to(converted(1.0));
+    }
+
+    #[test]
+    fn repeated_pairs_should_keep_their_order() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let batch: Vec<ConversionRequest> = (0..300)
+            .map(|quantity| match quantity % 3 {
+                0 => ConversionRequest::new(Unit::Kilo, Unit::Gram, quantity as f64),
+                1 => ConversionRequest::new(Unit::Gram, Unit::Kilo, quantity as f64),
+                _ => ConversionRequest::new(Unit::Kilo, Unit::Degree, quantity as f64),
+            })
+            .collect();
+
+        let response = client.post("/convert/batch").json(&batch).dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let results: Vec<BatchEntry> = response.into_json().expect("valid json array");
+        assert_that!(results).has_length(300);
+        for (quantity, entry) in results.into_iter().enumerate() {
+            let result = match entry {
+                BatchEntry::Converted(response) => Some(response.result),
+                BatchEntry::Failed(_) => None,
+            };
+            let expected = match quantity % 3 {
+                0 => Some(ConversionResult::Number(quantity as f64 * 1000.0)),
+                1 => Some(ConversionResult::Number(quantity as f64 / 1000.0)),
+                _ => None,
+            };
+            assert_that!(result).is_equal_to(expected);
+        }
+    }
+
+    #[test]
+    fn should_coerce_mismatched_elements_with_the_mismatch_policy() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let batch = json!([
+            {"from": "kilo", "to": "gram", "quantity": 1},
+            {"from": "kilo", "to": "degree", "quantity": 1},
+        ]);
+        let convert = |policy: &str| {
+            client
+                .post(format!("/convert/batch?on_mismatch={policy}"))
+                .json(&batch)
+                .dispatch()
+                .into_json::<Vec<BatchEntry>>()
+                .expect("valid json array")
+        };
+
+        let plain = |result| BatchEntry::Converted(ConversionResponse::plain(result));
+        assert_that!(convert("null")).is_equal_to(vec![
+            plain(ConversionResult::Number(1000.0)),
+            plain(ConversionResult::Null),
+        ]);
+        assert_that!(convert("zero")[1]).is_equal_to(plain(ConversionResult::Number(0.0)));
+        assert_that!(matches!(convert("error")[1], BatchEntry::Failed(_))).is_true();
+    }
+
+    #[test]
+    fn should_convert_elements_designated_by_configured_aliases() {
+        let figment =
+            rocket::Config::figment().merge(("aliases", HashMap::from([("pound", "lb")])));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let batch = json!([{"from": "pound", "to": "gram", "quantity": 1}]);
+
+        let response = client.post("/convert/batch").json(&batch).dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let entries: Vec<BatchEntry> = response.into_json().expect("valid json array");
+        let plain = BatchEntry::Converted(ConversionResponse::plain(ConversionResult::Number(
+            453.59237,
+        )));
+        assert_that!(entries).is_equal_to(vec![plain]);
+    }
+
+    #[test]
+    fn should_reject_batch_over_limit() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let batch = vec![ConversionRequest::new(Unit::Kilo, Unit::Gram, 1.0); 1001];
+
+        let response = client.post("/convert/batch").json(&batch).dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::BadRequest);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error).contains("at most 1000 conversions, got 1001");
+    }
+}
@@ -0,0 +1,232 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::conversion::RuleKind;
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::{system, Unit};
+use num_bigint::BigInt;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::State;
+use std::fmt;
+
+/// Quantities whose decimal exponent is further from zero are refused, they would be written
+/// with that many digits.
+const MAX_EXPONENT: i64 = 1000;
+
+/// Convert a quantity given as a decimal string, without going through `f64`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct BigConversionRequest {
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub from: Unit,
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub to: Unit,
+    pub quantity: String,
+}
+
+/// The exact result, as a decimal string.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct BigConversion {
+    pub result: String,
+}
+
+/// An arbitrary precision decimal number: `mantissa * 10^exponent`.
+#[derive(Debug, Clone, PartialEq)]
+struct BigDecimal {
+    mantissa: BigInt,
+    exponent: i64,
+}
+
+impl BigDecimal {
+    /// Parse a decimal number such as `-12.5` or `6.02e23`, whose exponent is at most
+    /// [`MAX_EXPONENT`].
+    fn parse(number: &str) -> Option<Self> {
+        let number = number.trim();
+        let (significand, exponent) = match number.split_once(['e', 'E']) {
+            Some((significand, exponent)) => (significand, exponent.parse::<i64>().ok()?),
+            None => (number, 0),
+        };
+        if exponent.abs() > MAX_EXPONENT {
+            return None;
+        }
+        let (integer, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+        let unsigned = integer.trim_start_matches(['+', '-']);
+        let digits_are_valid = |digits: &str| digits.bytes().all(|byte| byte.is_ascii_digit());
+        if (unsigned.is_empty() && fraction.is_empty())
+            || !digits_are_valid(unsigned)
+            || !digits_are_valid(fraction)
+            || integer.len() - unsigned.len() > 1
+        {
+            return None;
+        }
+
+        Some(BigDecimal {
+            mantissa: format!("{integer}{fraction}").parse().ok()?,
+            exponent: exponent.checked_sub(i64::try_from(fraction.len()).ok()?)?,
+        })
+    }
+
+    /// The exact decimal value of a float, from its shortest representation.
+    fn from_f64(number: f64) -> Option<Self> {
+        BigDecimal::parse(&number.to_string())
+    }
+
+    fn mul(&self, other: &BigDecimal) -> Self {
+        BigDecimal {
+            mantissa: &self.mantissa * &other.mantissa,
+            exponent: self.exponent + other.exponent,
+        }
+    }
+
+    /// The sum of both numbers, `None` if their exponents are too far apart to be aligned.
+    fn add(&self, other: &BigDecimal) -> Option<Self> {
+        let exponent = self.exponent.min(other.exponent);
+        Some(BigDecimal {
+            mantissa: self.scaled_mantissa(exponent)? + other.scaled_mantissa(exponent)?,
+            exponent,
+        })
+    }
+
+    /// The mantissa of this number written with the given, lower, exponent.
+    fn scaled_mantissa(&self, exponent: i64) -> Option<BigInt> {
+        let shift = u32::try_from(self.exponent.checked_sub(exponent)?).ok()?;
+        Some(&self.mantissa * BigInt::from(10).pow(shift))
+    }
+}
+
+impl fmt::Display for BigDecimal {
+    /// Write this number in positional notation, without trailing zeros.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.magnitude().to_string();
+        let sign = if self.mantissa.sign() == num_bigint::Sign::Minus {
+            "-"
+        } else {
+            ""
+        };
+
+        if self.exponent >= 0 {
+            let zeros = if digits == "0" { 0 } else { self.exponent };
+            return write!(f, "{sign}{digits}{}", "0".repeat(zeros as usize));
+        }
+
+        let decimals = self.exponent.unsigned_abs() as usize;
+        let padded = format!("{digits:0>width$}", width = decimals + 1);
+        let (integer, fraction) = padded.split_at(padded.len() - decimals);
+        match fraction.trim_end_matches('0') {
+            "" => write!(f, "{sign}{integer}"),
+            fraction => write!(f, "{sign}{integer}.{fraction}"),
+        }
+    }
+}
+
+/// Convert a quantity of any size or precision, applying the decimal value of the conversion
+/// factor and offset with arbitrary precision arithmetic, only available with the `bignum`
+/// feature. Conversions dividing by the quantity, such as `mpg` to `l/100km`, are refused.
+#[post("/convert/big", data = "<conversion>")]
+pub fn convert_big(
+    conversion: Json<BigConversionRequest>,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<BigConversion>, ConvertError<'static>> {
+    let BigConversionRequest { from, to, quantity } = conversion.into_inner();
+    let rule = from.rule_to(to, &converter)?;
+    if rule.kind() == RuleKind::Reciprocal {
+        return Err(ConvertError::NotLinear { from, to });
+    }
+
+    let parsed = BigDecimal::parse(&quantity).ok_or(ConvertError::InvalidQuantity(quantity))?;
+    let unusable = || ConvertError::UnusableRule { from, to };
+    let factor = BigDecimal::from_f64(rule.factor()).ok_or_else(unusable)?;
+    let offset = BigDecimal::from_f64(rule.offset()).ok_or_else(unusable)?;
+    let result = parsed.mul(&factor).add(&offset).ok_or_else(unusable)?;
+    quotas.consume(&api_key, 1)?;
+
+    Ok(Json(BigConversion {
+        result: result.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api_key::API_KEY_HEADER;
+    use crate::bignum::{BigConversion, BigDecimal};
+    use crate::rocket;
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_parse_and_display_decimals() {
+        let display = |number: &str| BigDecimal::parse(number).map(|number| number.to_string());
+
+        assert_that!(display("-12.50")).contains("-12.5".to_string());
+        assert_that!(display("0.001")).contains("0.001".to_string());
+        assert_that!(display("6.02e23")).contains("602000000000000000000000".to_string());
+        assert_that!(display("+.5")).contains("0.5".to_string());
+        assert_that!(display("1.5.2")).is_none();
+        assert_that!(display("--1")).is_none();
+        assert_that!(display("")).is_none();
+        assert_that!(display("1e1000000")).is_none();
+    }
+
+    #[test]
+    fn should_convert_huge_quantity_preserving_precision() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/big")
+            .json(&json!({"from": "gram", "to": "kilo", "quantity": "123456789012345678901234567890"}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let conversion: BigConversion = response.into_json().expect("valid json body");
+        assert_that!(conversion.result).is_equal_to("123456789012345678901234567.89".to_string());
+    }
+
+    #[test]
+    fn should_apply_offsets() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/big")
+            .json(&json!({"from": "celsius", "to": "fahrenheit", "quantity": "100.000000000000000000001"}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let conversion: BigConversion = response.into_json().expect("valid json body");
+        assert_that!(conversion.result).is_equal_to("212.0000000000000000000018".to_string());
+    }
+
+    #[test]
+    fn should_refuse_reciprocal_conversions() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/big")
+            .json(&json!({"from": "mpg", "to": "l/100km", "quantity": "30"}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn should_require_api_key() {
+        let figment = rocket::Config::figment().merge(("api_keys", ["paid"]));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let convert = |key: Option<&'static str>| {
+            let mut request = client
+                .post("/convert/big")
+                .json(&json!({"from": "gram", "to": "kilo", "quantity": "1"}));
+            if let Some(key) = key {
+                request = request.header(Header::new(API_KEY_HEADER, key));
+            }
+            request.dispatch().status()
+        };
+
+        assert_that!(convert(None)).is_equal_to(Status::Unauthorized);
+        assert_that!(convert(Some("paid"))).is_equal_to(Status::Ok);
+    }
+}
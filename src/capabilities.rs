@@ -0,0 +1,84 @@
+use crate::converter::Converter;
+use crate::Dimension;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+
+/// The optional features of this deployment, so clients can adapt to it.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct Capabilities {
+    /// The dimensions conversions are available for.
+    pub dimensions: Vec<Dimension>,
+    /// Whether `GET /debug/table` is compiled in, with the `debug` feature.
+    pub debug: bool,
+    /// Whether the `/admin` endpoints are compiled in, with the `admin` feature.
+    pub admin: bool,
+    /// Whether request spans are exported, with the `otel` feature.
+    pub otel: bool,
+    /// Whether `POST /convert/big` is compiled in, with the `bignum` feature.
+    pub bignum: bool,
+    /// Whether rules can be added with `POST /rules`, which needs a non-zero `max_custom_rules`.
+    pub runtime_rules: bool,
+    /// Whether some factors are overridden by the configured overrides file.
+    pub overrides: bool,
+    /// Whether `POST /convert` replays responses to requests sent with an `Idempotency-Key`.
+    pub idempotency: bool,
+    /// Whether large responses are compressed for clients accepting it.
+    pub compression: bool,
+}
+
+/// Report the optional features compiled in and enabled by the configuration.
+#[get("/capabilities")]
+pub fn capabilities(converter: Converter) -> Json<Capabilities> {
+    Json(Capabilities {
+        dimensions: Dimension::ALL.to_vec(),
+        debug: cfg!(feature = "debug"),
+        admin: cfg!(feature = "admin"),
+        otel: cfg!(feature = "otel"),
+        bignum: cfg!(feature = "bignum"),
+        runtime_rules: converter.config.max_custom_rules > 0,
+        overrides: converter.config.overrides_file.is_some(),
+        idempotency: true,
+        compression: true,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rocket;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_report_known_capabilities() {
+        let figment = rocket::Config::figment().merge(("max_custom_rules", 0));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let response = client.get("/capabilities").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        for key in [
+            "dimensions",
+            "debug",
+            "admin",
+            "otel",
+            "bignum",
+            "runtime_rules",
+            "overrides",
+            "idempotency",
+            "compression",
+        ] {
+            asserting!(key).that(&body.get(key)).is_some();
+        }
+        let temperature = Value::from("temperature");
+        assert_that!(body["dimensions"]
+            .as_array()
+            .map(|dimensions| dimensions.contains(&temperature)))
+        .contains(true);
+        assert_that!(body["runtime_rules"].as_bool()).contains(false);
+        assert_that!(body["admin"].as_bool()).contains(cfg!(feature = "admin"));
+    }
+}
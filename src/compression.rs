@@ -0,0 +1,232 @@
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression as Level;
+use rocket::fairing::{AdHoc, Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::{Request, Response};
+use std::io::{Cursor, Write};
+
+/// Size, in bytes, under which responses are not compressed when no `compression_threshold`
+/// is configured.
+const DEFAULT_THRESHOLD: usize = 1024;
+
+/// Attach the [`Compression`] fairing, compressing responses larger than the configured
+/// `compression_threshold`.
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Compression", |rocket| async {
+        let threshold = rocket
+            .figment()
+            .extract_inner("compression_threshold")
+            .unwrap_or(DEFAULT_THRESHOLD);
+
+        rocket.attach(Compression { threshold })
+    })
+}
+
+/// A content coding supported by the [`Compression`] fairing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Encoding {
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    /// The encoding with the highest quality value in an `Accept-Encoding` header, gzip over
+    /// deflate when they are equally preferred. Codings not listed get the quality of `*`, if any.
+    fn negotiate(accept_encoding: &str) -> Option<Self> {
+        let codings: Vec<(&str, f32)> = accept_encoding
+            .split(',')
+            .filter_map(|coding| {
+                let mut params = coding.split(';').map(str::trim);
+                let name = params.next().filter(|name| !name.is_empty())?;
+                let quality = match params.find_map(|param| {
+                    param
+                        .strip_prefix("q=")
+                        .or_else(|| param.strip_prefix("Q="))
+                }) {
+                    Some(quality) => quality.parse().ok()?,
+                    None => 1.0,
+                };
+                Some((name, quality))
+            })
+            .collect();
+        let quality = |name: &str| {
+            codings
+                .iter()
+                .find(|(coding, _)| coding.eq_ignore_ascii_case(name))
+                .map(|&(_, quality)| quality)
+        };
+        let wildcard = quality("*").unwrap_or(0.0);
+
+        [Encoding::Gzip, Encoding::Deflate]
+            .into_iter()
+            .map(|encoding| (encoding, quality(encoding.name()).unwrap_or(wildcard)))
+            .filter(|&(_, quality)| quality > 0.0)
+            .fold(None, |best, (encoding, quality)| match best {
+                Some((_, best_quality)) if best_quality >= quality => best,
+                _ => Some((encoding, quality)),
+            })
+            .map(|(encoding, _)| encoding)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    fn encode(&self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(vec![], Level::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+            // The `deflate` coding is zlib wrapped deflate data (RFC 9110 section 8.4.1.2)
+            Encoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(vec![], Level::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Compress responses honoring the request `Accept-Encoding` header.
+///
+/// Only responses of a known size above `threshold` bytes are compressed,
+/// streamed responses are left untouched so they are still sent as they are produced.
+pub struct Compression {
+    threshold: usize,
+}
+
+#[rocket::async_trait]
+impl Fairing for Compression {
+    fn info(&self) -> Info {
+        Info {
+            name: "Response compression",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let size = response.body().preset_size();
+        if response.headers().contains("Content-Encoding")
+            || size.is_none_or(|size| size < self.threshold)
+        {
+            return;
+        }
+
+        response.adjoin_header(Header::new("Vary", "Accept-Encoding"));
+        let Some(encoding) = request
+            .headers()
+            .get_one("Accept-Encoding")
+            .and_then(Encoding::negotiate)
+        else {
+            return;
+        };
+
+        let body = match response.body_mut().to_bytes().await {
+            Ok(body) => body,
+            Err(err) => {
+                error!("Failed to read response body: {}", err);
+                return;
+            }
+        };
+
+        match encoding.encode(&body) {
+            Ok(compressed) => {
+                response.set_header(Header::new("Content-Encoding", encoding.name()));
+                response.set_sized_body(compressed.len(), Cursor::new(compressed));
+            }
+            Err(err) => {
+                error!("Failed to compress response: {}", err);
+                response.set_sized_body(body.len(), Cursor::new(body));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Encoding;
+    use crate::batch::BatchEntry;
+    use crate::{rocket, ConversionRequest, Unit};
+    use flate2::read::{GzDecoder, ZlibDecoder};
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json;
+    use speculoos::prelude::*;
+    use std::io::Read;
+
+    #[test]
+    fn should_negotiate_encoding() {
+        assert_that!(Encoding::negotiate("deflate, gzip;q=0.8")).contains(Encoding::Deflate);
+        assert_that!(Encoding::negotiate("deflate;q=0.5, gzip")).contains(Encoding::Gzip);
+        assert_that!(Encoding::negotiate("deflate, gzip")).contains(Encoding::Gzip);
+        assert_that!(Encoding::negotiate("gzip;q=0, deflate")).contains(Encoding::Deflate);
+        assert_that!(Encoding::negotiate("br")).is_none();
+    }
+
+    #[test]
+    fn should_negotiate_wildcard() {
+        assert_that!(Encoding::negotiate("*")).contains(Encoding::Gzip);
+        assert_that!(Encoding::negotiate("br, *;q=0.5")).contains(Encoding::Gzip);
+        assert_that!(Encoding::negotiate("gzip;q=0.2, *;q=0.5")).contains(Encoding::Deflate);
+        assert_that!(Encoding::negotiate("gzip;q=0, *")).contains(Encoding::Deflate);
+        assert_that!(Encoding::negotiate("*;q=0")).is_none();
+    }
+
+    #[test]
+    fn should_compress_with_zlib_deflate() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let batch = vec![ConversionRequest::new(Unit::Kilo, Unit::Gram, 1.0); 1000];
+
+        let response = client
+            .post("/convert/batch")
+            .header(Header::new("Accept-Encoding", "deflate"))
+            .json(&batch)
+            .dispatch();
+
+        assert_that!(response.headers().get_one("Content-Encoding")).contains("deflate");
+        let compressed = response.into_bytes().expect("response body");
+        let mut body = String::new();
+        ZlibDecoder::new(compressed.as_slice())
+            .read_to_string(&mut body)
+            .expect("valid zlib body");
+        let entries: Vec<BatchEntry> = serde_json::from_str(&body).expect("valid json array");
+        assert_that!(entries).has_length(1000);
+    }
+
+    #[test]
+    fn should_compress_large_batch_response() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let batch = vec![ConversionRequest::new(Unit::Kilo, Unit::Gram, 1.0); 1000];
+
+        let response = client
+            .post("/convert/batch")
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .json(&batch)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        assert_that!(response.headers().get_one("Content-Encoding")).contains("gzip");
+        let compressed = response.into_bytes().expect("response body");
+        let mut body = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut body)
+            .expect("valid gzip body");
+        let entries: Vec<BatchEntry> = serde_json::from_str(&body).expect("valid json array");
+        assert_that!(entries).has_length(1000);
+    }
+
+    #[test]
+    fn should_not_compress_without_accept_encoding() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let batch = vec![ConversionRequest::new(Unit::Kilo, Unit::Gram, 1.0); 1000];
+
+        let response = client.post("/convert/batch").json(&batch).dispatch();
+
+        assert_that!(response.headers().get_one("Content-Encoding")).is_none();
+    }
+}
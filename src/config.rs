@@ -0,0 +1,136 @@
+use crate::format::DEFAULT_DECIMALS;
+use crate::{Dimension, Unit};
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Application settings, extracted from the Rocket configuration
+/// (`Rocket.toml` or `ROCKET_*` environment variables).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct Config {
+    /// Locale used to display string results.
+    pub locale: Locale,
+    /// Conversions refused with a `403 Forbidden` error.
+    pub disabled_conversions: Vec<UnitPair>,
+    /// Maximum number of conversions in a single batch request.
+    pub max_batch_size: usize,
+    /// Maximum number of points in a single sweep request.
+    pub max_sweep_points: usize,
+    /// A TOML or JSON file of conversion rules, taking precedence over the built-in ones.
+    pub overrides_file: Option<PathBuf>,
+    /// How close to an integer a result must be to be snapped to it.
+    pub snap_epsilon: f64,
+    /// Additional unit names, e.g. `{ kilogram = "kilo" }`.
+    pub aliases: HashMap<String, Unit>,
+    /// Maximum number of rules added with `POST /rules`.
+    pub max_custom_rules: usize,
+    /// How conversions with a subnormal result are handled.
+    pub subnormal_results: SubnormalPolicy,
+    /// Smaller results are replaced with this bound.
+    pub min_result: Option<f64>,
+    /// Larger results are replaced with this bound.
+    pub max_result: Option<f64>,
+    /// Convert from the base unit of the target dimension when `POST /convert` omits `from`.
+    pub default_from_base_unit: bool,
+    /// The number of decimal digits results are rounded to, keyed by dimension.
+    /// Dimensions missing from it keep [`DEFAULT_DECIMALS`].
+    pub precision: HashMap<Dimension, usize>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            locale: Locale::default(),
+            disabled_conversions: vec![],
+            max_batch_size: 1000,
+            max_sweep_points: 1000,
+            overrides_file: None,
+            snap_epsilon: 0.000001,
+            aliases: HashMap::new(),
+            max_custom_rules: 100,
+            subnormal_results: SubnormalPolicy::default(),
+            min_result: None,
+            max_result: None,
+            default_from_base_unit: false,
+            precision: HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Whether the conversion from `from` to `to` has been disabled.
+    pub fn is_disabled(&self, from: Unit, to: Unit) -> bool {
+        self.disabled_conversions
+            .iter()
+            .any(|pair| pair.from == from && pair.to == to)
+    }
+
+    /// The number of decimal digits results measuring `dimension` are rounded to.
+    pub fn decimals(&self, dimension: Dimension) -> usize {
+        self.precision
+            .get(&dimension)
+            .copied()
+            .unwrap_or(DEFAULT_DECIMALS)
+    }
+
+    /// Clamp a result to the configured `min_result` and `max_result`, returning whether it
+    /// was out of these bounds.
+    pub fn clamp(&self, result: f64) -> (f64, bool) {
+        match (self.min_result, self.max_result) {
+            (Some(min), _) if result < min => (min, true),
+            (_, Some(max)) if result > max => (max, true),
+            _ => (result, false),
+        }
+    }
+}
+
+/// A conversion from one unit to another.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct UnitPair {
+    pub from: Unit,
+    pub to: Unit,
+}
+
+/// How conversions are handled when their exact result is a subnormal float, too small
+/// to be represented with full precision and mishandled by some JSON consumers.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum SubnormalPolicy {
+    /// Return the subnormal result as is.
+    #[default]
+    Keep,
+    /// Replace the result with a zero of the same sign, logging a warning.
+    Flush,
+    /// Refuse the conversion with a `422 Unprocessable Entity` error.
+    Reject,
+}
+
+/// A locale, defining how numbers are displayed.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+    De,
+}
+
+impl Locale {
+    pub fn thousands_separator(self) -> &'static str {
+        match self {
+            Locale::En => ",",
+            // Narrow no-break space
+            Locale::Fr => "\u{202f}",
+            Locale::De => ".",
+        }
+    }
+
+    pub fn decimal_separator(self) -> &'static str {
+        match self {
+            Locale::En => ".",
+            Locale::Fr | Locale::De => ",",
+        }
+    }
+}
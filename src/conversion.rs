@@ -1,72 +1,287 @@
 use crate::{ConvertError, Unit};
 use once_cell::sync::Lazy;
-use std::collections::HashSet;
+use rocket::serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use wide::f64x4;
 
-const KNOWN_CONVERSIONS: [[&str; 3]; 4] = [
+// Conversions between metric units are derived from their SI prefix,
+// only conversions involving other units need to be listed here.
+// Only one direction is listed, the table computes the reverse rule as the exact reciprocal
+// of the factor rather than relying on a truncated literal.
+const KNOWN_CONVERSIONS: [[&str; 3]; 16] = [
+    // International avoirdupois pound, exact by definition
     ["lb", "kg", "0.45359237"],
-    ["lb", "g", "453.59237"],
-    ["kg", "lb", "2.20462262"],
-    ["kg", "metric ton", "0.001"],
+    // 2000 lb
+    ["short ton", "kg", "907.18474"],
+    // 2240 lb
+    ["long ton", "kg", "1016.0469088"],
+    // 14 lb
+    ["st", "kg", "6.35029318"],
+    ["oz", "g", "28.349523125"],
+    // π / 180
+    ["deg", "rad", "0.017453292519943295"],
+    // 10 / 9
+    ["deg", "grad", "1.1111111111111112"],
+    ["bar", "Pa", "100000"],
+    ["psi", "Pa", "6894.757"],
+    ["cal", "J", "4.184"],
+    ["kWh", "J", "3600000"],
+    // Avogadro constant
+    ["mol", "count", "6.02214076e23"],
+    ["kHz", "Hz", "1000"],
+    // 1 / 60
+    ["rpm", "Hz", "0.016666666666666666"],
+    ["min", "s", "60"],
+    ["h", "s", "3600"],
 ];
 
-pub static CONVERSION_TABLE: Lazy<HashSet<ConversionRule>> = Lazy::new(|| {
-    // given k=4 (the number of unit) and n=2 (a conversion pair) we have a total of k^n permutations
-    let permutations = 4_i32.pow(2) as usize;
+// Conversions applying `factor / quantity` rather than `factor * quantity`
+const KNOWN_RECIPROCAL_CONVERSIONS: [[&str; 3]; 1] = [
+    // 100 km / (1 mile / 3.785411784 l)
+    ["mpg", "L/100km", "235.2145833"],
+];
+
+// Conversions applying `factor * quantity + offset`, between units having different origins
+const KNOWN_OFFSET_CONVERSIONS: [[&str; 4]; 3] = [
+    ["°C", "°F", "1.8", "32"],
+    ["°C", "K", "1", "273.15"],
+    // Gauge pressure is relative to the standard atmosphere
+    ["psig", "psi", "1", "14.696"],
+];
+
+/// Version of the built-in conversion factors, bumped whenever a known conversion changes.
+pub const FACTORS_VERSION: &str = "1";
+
+/// The number of rules a complete conversion table holds: given k (the number of unit in a dimension)
+/// and n=2 (a conversion pair) we have a total of k^n permutations per dimension.
+pub fn expected_rule_count() -> usize {
+    let mut units_per_dimension = HashMap::new();
+    for unit in Unit::ALL {
+        *units_per_dimension.entry(unit.dimension()).or_insert(0) += 1;
+    }
+
+    units_per_dimension.values().map(|k: &usize| k.pow(2)).sum()
+}
 
-    // We will rely on ConversionRule Hash implementation to generate all possible rules.
-    // Since we only have a total of 16 permutations we will use a simple greedy algorithm
-    // to find all permutations.
-    let mut rules = HashSet::with_capacity(permutations);
+/// The conversion table built from the known conversions only.
+pub static CONVERSION_TABLE: Lazy<ConversionTable> = Lazy::new(|| ConversionTable::build(&[]));
+
+/// The known conversions, the reciprocal ones and the ones with an offset.
+fn known_rules() -> impl Iterator<Item = ConversionRule> {
+    let offset_rules = KNOWN_OFFSET_CONVERSIONS.iter().filter_map(|rule| {
+        let seed = [rule[0], rule[1], rule[2]];
+        ConversionRule::try_from(&seed)
+            .ok()
+            .zip(rule[3].parse().ok())
+            .map(|(seeded, offset)| ConversionRule { offset, ..seeded })
+    });
+
+    let reciprocal_rules = KNOWN_RECIPROCAL_CONVERSIONS
+        .iter()
+        .map(ConversionRule::try_from)
+        .filter_map(Result::ok)
+        .map(|rule| ConversionRule {
+            kind: RuleKind::Reciprocal,
+            ..rule
+        });
 
-    // Insert known rules and their counter part in the conversion table
     KNOWN_CONVERSIONS
         .iter()
         .map(ConversionRule::try_from)
         .filter_map(Result::ok)
-        .for_each(|rule| {
+        .chain(reciprocal_rules)
+        .chain(offset_rules)
+}
+
+/// The units missing a conversion rule to some other unit of their dimension.
+fn incomplete_units(rules: &HashSet<ConversionRule>) -> Vec<Unit> {
+    Unit::ALL
+        .into_iter()
+        .filter(|unit| {
+            let dimension_units = Unit::ALL
+                .into_iter()
+                .filter(|other| other.dimension() == unit.dimension())
+                .count();
+            rules.iter().filter(|rule| rule.from == *unit).count() < dimension_units
+        })
+        .collect()
+}
+
+/// A complete conversion table, holding a rule for every pair of units of the same dimension.
+#[derive(Debug, Clone)]
+pub struct ConversionTable {
+    rules: HashSet<ConversionRule>,
+}
+
+impl ConversionTable {
+    /// Build the conversion table from the known conversions, `overrides` taking precedence
+    /// over them. Every override must convert between two units of the same dimension.
+    pub fn build(overrides: &[ConversionRule]) -> Self {
+        let permutations = expected_rule_count();
+
+        // We will rely on ConversionRule Hash implementation to generate all possible rules.
+        // Since we only have a few permutations we will use a simple greedy algorithm
+        // to find all permutations.
+        let mut rules = HashSet::with_capacity(permutations);
+
+        // Insert overrides then known rules, and their counter part in the conversion table.
+        // Rules already in the table are never replaced, so overrides come first, and the
+        // inverse of an override never shadows an override listed in the other direction.
+        let overrides: Vec<ConversionRule> = overrides
+            .iter()
+            .map(|rule| ConversionRule {
+                provenance: Provenance::Overridden,
+                ..*rule
+            })
+            .collect();
+        rules.extend(overrides.iter().copied());
+        overrides
+            .into_iter()
+            .map(ConversionRule::invert)
+            .for_each(|rule| {
+                rules.insert(rule);
+            });
+        known_rules().for_each(|rule| {
             let invert_rule = rule.invert();
             rules.insert(rule);
             rules.insert(invert_rule);
         });
 
-    loop {
-        // Fill the conversion table until we have all the possible permutations
-        if rules.len() == permutations {
-            break;
+        // Insert every metric to metric rule, computed from the units SI prefix
+        for from in Unit::ALL {
+            for to in Unit::ALL {
+                if let (Some(from_prefix), Some(to_prefix)) = (from.prefix(), to.prefix()) {
+                    rules.insert(ConversionRule {
+                        from,
+                        to,
+                        factor: from_prefix.factor_to(to_prefix),
+                        offset: 0.0,
+                        kind: RuleKind::Affine,
+                        provenance: Provenance::Derived,
+                    });
+                }
+            }
         }
 
-        let current_rules: HashSet<ConversionRule> = rules.clone();
+        loop {
+            // Fill the conversion table until we have all the possible permutations
+            if rules.len() == permutations {
+                break;
+            }
+
+            let current_rules: HashSet<ConversionRule> = rules.clone();
 
-        // Find possible rule combination and generate a new one plus its inversion
-        for rule in &current_rules {
-            for other in &current_rules {
-                if other.from == rule.to {
-                    let rule = rule.combine(other);
-                    if !rules.contains(&rule) {
-                        let inverted = rule.invert();
+            // Find possible rule combination and generate a new one plus its inversion
+            for rule in &current_rules {
+                for other in &current_rules {
+                    if other.from == rule.to {
+                        let rule = rule.combine(other);
+                        if !rules.contains(&rule) {
+                            let inverted = rule.invert();
 
-                        rules.insert(rule);
-                        if !rules.contains(&inverted) {
-                            rules.insert(inverted);
+                            rules.insert(rule);
+                            if !rules.contains(&inverted) {
+                                rules.insert(inverted);
+                            }
                         }
                     }
                 }
             }
+
+            // A unit with no known conversion to the rest of its dimension would make us loop forever
+            if rules.len() == current_rules.len() {
+                panic!(
+                    "Conversion table cannot be completed, add a known conversion for {:?}",
+                    incomplete_units(&rules)
+                );
+            }
         }
+
+        ConversionTable { rules }
+    }
+
+    /// Find the rule converting `from` to `to`.
+    pub fn find(&self, from: Unit, to: Unit) -> Option<&ConversionRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.from == from && rule.to == to)
+    }
+
+    /// Find the rule converting `from` to `to`, falling back to the combination of the rules
+    /// converting `from` to the base unit of its dimension and the base unit to `to`
+    /// when the table holds no direct rule.
+    pub fn find_or_through_base(&self, from: Unit, to: Unit) -> Option<ConversionRule> {
+        self.find(from, to)
+            .copied()
+            .or_else(|| self.find_through(from, from.dimension().base_unit(), to))
+    }
+
+    /// Build the rule converting `from` to `to` by way of `through`, combining the rules
+    /// converting `from` to `through` and `through` to `to`.
+    pub fn find_through(&self, from: Unit, through: Unit, to: Unit) -> Option<ConversionRule> {
+        Some(self.find(from, through)?.combine(self.find(through, to)?))
+    }
+
+    /// The number of rules in the table.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Every rule of the table, in no particular order.
+    pub fn rules(&self) -> impl Iterator<Item = &ConversionRule> {
+        self.rules.iter()
     }
+}
 
-    rules
-});
+/// Where a conversion rule comes from.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum Provenance {
+    /// One of the built-in known conversions, or its exact reciprocal.
+    Seeded,
+    /// Read from the conversion overrides file, or added with `POST /rules`, or its exact
+    /// reciprocal.
+    Overridden,
+    /// Combined from other rules or computed from the units SI prefix.
+    #[default]
+    Derived,
+}
+
+/// How a conversion rule transforms the quantity.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum RuleKind {
+    /// `factor * quantity + offset`
+    #[default]
+    Affine,
+    /// `factor / quantity`, for inversely related units such as mpg and l/100km.
+    Reciprocal,
+}
 
-/// A conversion  from a given unit to the target unit.
-#[derive(Copy, Clone, Debug)]
+/// A conversion  from a given unit to the target unit, either an affine or a reciprocal
+/// function of the quantity (see [`RuleKind`]).
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
 pub struct ConversionRule {
     /// The unit to convert from.
     pub from: Unit,
     /// Target unit of the conversion rule.
     pub to: Unit,
     factor: f64,
+    /// Added after applying the factor, zero for units sharing the same origin.
+    /// Reciprocal rules have no offset.
+    #[serde(default)]
+    offset: f64,
+    #[serde(default)]
+    kind: RuleKind,
+    #[serde(skip)]
+    provenance: Provenance,
 }
 
 impl PartialEq for ConversionRule {
@@ -81,34 +296,124 @@ impl Hash for ConversionRule {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.from.hash(state);
         self.to.hash(state);
-        state.finish();
     }
 }
 
 impl<'a> TryFrom<&'a [&'a str; 3]> for ConversionRule {
     type Error = ConvertError<'a>;
     fn try_from(rule: &'a [&'a str; 3]) -> Result<Self, ConvertError<'a>> {
-        Ok(ConversionRule {
+        let rule = ConversionRule {
             from: rule[0].try_into()?,
             to: rule[1].try_into()?,
             factor: rule[2]
                 .parse()
-                .expect("Conversion from table rule should never fail"),
-        })
+                .map_err(|_| ConvertError::UnparsableFactor(rule[2]))?,
+            offset: 0.0,
+            kind: RuleKind::Affine,
+            provenance: Provenance::Seeded,
+        };
+        rule.check_factor()?;
+        Ok(rule)
     }
 }
 
 impl ConversionRule {
-    /// Apply the conversion factor to the given quantity
+    /// Refuse rules whose factor is zero, negative or not finite: they would convert every
+    /// quantity to the offset, reverse its sign, or have no meaningful inverse.
+    pub(crate) fn check_factor(&self) -> Result<(), ConvertError<'static>> {
+        if self.factor > 0.0 && self.factor.is_finite() {
+            Ok(())
+        } else {
+            Err(ConvertError::InvalidFactor {
+                from: self.from,
+                to: self.to,
+                factor: self.factor,
+            })
+        }
+    }
+
+    /// Apply the conversion factor and offset to the given quantity
     pub(crate) fn convert(&self, quantity: f64) -> f64 {
-        self.factor * quantity
+        match self.kind {
+            RuleKind::Affine => self.factor * quantity + self.offset,
+            RuleKind::Reciprocal => self.factor / quantity,
+        }
+    }
+
+    /// Convert every quantity in place, four at a time with SIMD instructions. Slices shorter
+    /// than a SIMD vector, and the last quantities of the others, are converted one by one.
+    ///
+    /// Both paths compute `factor * quantity + offset` (or `factor / quantity`) with the same
+    /// IEEE 754 operations, in the same order and without fused multiply-add, so the results
+    /// are bit-identical to [`ConversionRule::convert`].
+    pub(crate) fn convert_slice(&self, quantities: &mut [f64]) {
+        let factor = f64x4::splat(self.factor);
+        let offset = f64x4::splat(self.offset);
+
+        let mut chunks = quantities.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            let quantity = f64x4::from(&*chunk);
+            let converted = match self.kind {
+                RuleKind::Affine => factor * quantity + offset,
+                RuleKind::Reciprocal => factor / quantity,
+            };
+            chunk.copy_from_slice(&converted.to_array());
+        }
+
+        for quantity in chunks.into_remainder() {
+            *quantity = self.convert(*quantity);
+        }
+    }
+
+    /// Convert with decimal arithmetic, from the exact values of the quantity, factor and offset.
+    /// `None` when one of them or the result does not fit in a decimal.
+    pub(crate) fn convert_exactly(&self, quantity: f64) -> Option<Decimal> {
+        let factor = Decimal::from_f64_retain(self.factor)?;
+        let quantity = Decimal::from_f64_retain(quantity)?;
+        match self.kind {
+            RuleKind::Affine => factor
+                .checked_mul(quantity)?
+                .checked_add(Decimal::from_f64_retain(self.offset)?),
+            RuleKind::Reciprocal => factor.checked_div(quantity),
+        }
     }
 
+    /// The factor applied by this rule.
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    /// The offset added by this rule.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// How this rule transforms the quantity.
+    pub fn kind(&self) -> RuleKind {
+        self.kind
+    }
+
+    /// Where this rule comes from.
+    pub fn provenance(&self) -> Provenance {
+        self.provenance
+    }
+
+    /// The rule converting back, with the same provenance: the inverse of a seeded rule
+    /// is computed at runtime as its exact reciprocal, and is as exact as the rule itself.
     fn invert(self) -> ConversionRule {
+        let (factor, offset) = match self.kind {
+            RuleKind::Affine => (1.0 / self.factor, -self.offset / self.factor),
+            // y = k / x is its own inverse: x = k / y
+            RuleKind::Reciprocal => (self.factor, 0.0),
+        };
+
         ConversionRule {
             from: self.to,
             to: self.from,
-            factor: 1.0 / self.factor,
+            factor,
+            offset,
+            kind: self.kind,
+            provenance: self.provenance,
         }
     }
 
@@ -117,10 +422,27 @@ impl ConversionRule {
         let from = self.from;
         let to = other.to;
 
-        let factor = if from == to {
-            1.0
-        } else {
-            self.factor * other.factor
+        // Offsets are never combined with reciprocal rules, the result would neither be
+        // an affine nor a reciprocal function of the quantity.
+        let (kind, factor, offset) = match (self.kind, other.kind) {
+            _ if from == to => (RuleKind::Affine, 1.0, 0.0),
+            (RuleKind::Affine, RuleKind::Affine) => (
+                RuleKind::Affine,
+                self.factor * other.factor,
+                other.factor * self.offset + other.offset,
+            ),
+            // k / (a * x)
+            (RuleKind::Affine, RuleKind::Reciprocal) => {
+                (RuleKind::Reciprocal, other.factor / self.factor, 0.0)
+            }
+            // a * (k / x)
+            (RuleKind::Reciprocal, RuleKind::Affine) => {
+                (RuleKind::Reciprocal, self.factor * other.factor, 0.0)
+            }
+            // k2 / (k1 / x)
+            (RuleKind::Reciprocal, RuleKind::Reciprocal) => {
+                (RuleKind::Affine, other.factor / self.factor, 0.0)
+            }
         };
 
         // Unfortunately some rule combination give slightly imprecise results
@@ -132,18 +454,34 @@ impl ConversionRule {
             factor
         };
 
-        ConversionRule { from, to, factor }
+        ConversionRule {
+            from,
+            to,
+            factor,
+            offset,
+            kind,
+            provenance: Provenance::Derived,
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::conversion::{
+        known_rules, ConversionRule, ConversionTable, Provenance, RuleKind, CONVERSION_TABLE,
+    };
+    use crate::converter::Converter;
+    use crate::error::ConvertError;
     use crate::ConversionRequest;
-    use crate::Unit;
+    use crate::{Dimension, Unit};
     use speculoos::prelude::*;
+    use std::collections::HashSet;
+    use std::sync::Arc;
 
     fn test_conversion(from: Unit, to: Unit, quantity: f64) -> f64 {
-        ConversionRequest { from, to, quantity }.execute()
+        ConversionRequest::new(from, to, quantity)
+            .execute(&Converter::default())
+            .expect("valid conversion")
     }
 
     #[test]
@@ -170,6 +508,29 @@ mod test {
         assert_that!(result).is_close_to(2.20462262, 0.00001);
     }
 
+    #[test]
+    fn kilo_to_pound_and_back_should_be_exact() {
+        for quantity in [1.0, 2.5, 13.0, 100.0, 1234.5678, 987_654.321] {
+            let pounds = test_conversion(Unit::Kilo, Unit::Lb, quantity);
+            assert_that!(test_conversion(Unit::Lb, Unit::Kilo, pounds)).is_equal_to(quantity);
+        }
+        assert_that!(CONVERSION_TABLE
+            .find(Unit::Kilo, Unit::Lb)
+            .map(ConversionRule::factor))
+        .contains(1.0 / 0.45359237);
+    }
+
+    #[test]
+    fn known_conversions_should_list_one_direction_per_pair() {
+        let pairs: Vec<(Unit, Unit)> = known_rules().map(|rule| (rule.from, rule.to)).collect();
+        for &(from, to) in &pairs {
+            let description = format!("{from:?} to {to:?} is already listed in reverse");
+            asserting!(&description)
+                .that(&pairs.contains(&(to, from)))
+                .is_false();
+        }
+    }
+
     #[test]
     fn from_kilo_to_gram() {
         let result = test_conversion(Unit::Kilo, Unit::Gram, 1.0);
@@ -215,6 +576,458 @@ mod test {
     #[test]
     fn from_ton_to_gram() {
         let result = test_conversion(Unit::Ton, Unit::Gram, 1.0);
-        assert_that!(result).is_close_to(1000_000.0, 0.00001);
+        assert_that!(result).is_close_to(1_000_000.0, 0.00001);
+    }
+
+    #[test]
+    fn from_milligram_to_gram() {
+        let from = Unit::try_from("mg").expect("valid unit");
+        let result = test_conversion(from, Unit::Gram, 1000.0);
+        assert_that!(result).is_close_to(1.0, 0.00001);
+    }
+
+    #[test]
+    fn from_pound_to_microgram() {
+        let result = test_conversion(Unit::Lb, Unit::Microgram, 1.0);
+        assert_that!(result).is_close_to(453_592_370.0, 0.00001);
+    }
+
+    #[test]
+    fn from_stone_to_pound() {
+        let result = test_conversion(Unit::Stone, Unit::Lb, 1.0);
+        assert_that!(result).is_close_to(14.0, 0.0000001);
+    }
+
+    #[test]
+    fn from_ounce_to_gram() {
+        let result = test_conversion(Unit::Ounce, Unit::Gram, 1.0);
+        assert_that!(result).is_close_to(28.34952312, 0.0000001);
+    }
+
+    #[test]
+    fn from_degree_to_radian() {
+        let result = test_conversion(Unit::Degree, Unit::Radian, 180.0);
+        assert_that!(result).is_close_to(std::f64::consts::PI, 0.00000001);
+    }
+
+    #[test]
+    fn from_degree_to_gradian() {
+        let result = test_conversion(Unit::Degree, Unit::Gradian, 90.0);
+        assert_that!(result).is_close_to(100.0, 0.00001);
+    }
+
+    #[test]
+    fn from_radian_to_gradian() {
+        let result = test_conversion(Unit::Radian, Unit::Gradian, std::f64::consts::PI);
+        assert_that!(result).is_close_to(200.0, 0.00001);
+    }
+
+    #[test]
+    fn from_pound_to_gram_through_kilo() {
+        let request = ConversionRequest::new(Unit::Lb, Unit::Gram, 1.0).through(Unit::Kilo);
+        let result = request
+            .execute(&Converter::default())
+            .expect("valid conversion");
+        assert_that!(result).is_close_to(test_conversion(Unit::Lb, Unit::Gram, 1.0), 0.00000001);
+    }
+
+    #[test]
+    fn through_unit_of_another_dimension_should_fail() {
+        let request = ConversionRequest::new(Unit::Lb, Unit::Gram, 1.0).through(Unit::Degree);
+        assert_that!(request.execute(&Converter::default())).is_err();
+    }
+
+    #[test]
+    fn overrides_should_take_precedence() {
+        let overrides = [ConversionRule {
+            from: Unit::Kilo,
+            to: Unit::Lb,
+            factor: 2.0,
+            offset: 0.0,
+            kind: RuleKind::Affine,
+            provenance: Provenance::Seeded,
+        }];
+
+        let table = ConversionTable::build(&overrides);
+
+        let factor = |from, to| table.find(from, to).map(ConversionRule::factor);
+        assert_that!(factor(Unit::Kilo, Unit::Lb)).contains(2.0);
+        assert_that!(factor(Unit::Lb, Unit::Kilo)).contains(0.5);
+        assert_that!(factor(Unit::Gram, Unit::Lb)).contains(0.002);
+    }
+
+    #[test]
+    fn from_bar_to_psi() {
+        let result = test_conversion(Unit::Bar, Unit::Psi, 1.0);
+        assert_that!(result).is_close_to(14.5037738, 0.00001);
+    }
+
+    #[test]
+    fn from_psi_to_bar() {
+        let result = test_conversion(Unit::Psi, Unit::Bar, 1.0);
+        assert_that!(result).is_close_to(0.06894757, 0.00000001);
+    }
+
+    #[test]
+    fn offsets_should_be_inverted_and_combined() {
+        // A gauge pressure reading in bar, relative to an atmospheric pressure of 1.01325 bar
+        let gauge_to_bar = ConversionRule {
+            from: Unit::Psi,
+            to: Unit::Bar,
+            factor: 1.0,
+            offset: 1.01325,
+            kind: RuleKind::Affine,
+            provenance: Provenance::Seeded,
+        };
+        let bar_to_pascal = ConversionRule {
+            from: Unit::Bar,
+            to: Unit::Pascal,
+            factor: 100_000.0,
+            offset: 0.0,
+            kind: RuleKind::Affine,
+            provenance: Provenance::Seeded,
+        };
+
+        let gauge_to_pascal = gauge_to_bar.combine(&bar_to_pascal);
+        let pascal_to_gauge = gauge_to_pascal.invert();
+
+        assert_that!(gauge_to_pascal.convert(1.0)).is_close_to(201_325.0, 0.00001);
+        assert_that!(pascal_to_gauge.convert(201_325.0)).is_close_to(1.0, 0.00001);
+        assert_that!(gauge_to_bar.combine(&gauge_to_bar.invert()).convert(2.0)).is_equal_to(2.0);
+    }
+
+    #[test]
+    fn from_mpg_to_liters_per_100_km() {
+        let result = test_conversion(Unit::Mpg, Unit::LPer100Km, 30.0);
+        assert_that!(result).is_close_to(7.84048611, 0.00000001);
+    }
+
+    #[test]
+    fn from_liters_per_100_km_to_mpg() {
+        let result = test_conversion(Unit::LPer100Km, Unit::Mpg, 7.84048611);
+        assert_that!(result).is_close_to(30.0, 0.000001);
+    }
+
+    #[test]
+    fn from_kilowatt_hour_to_joule() {
+        let result = test_conversion(Unit::KilowattHour, Unit::Joule, 1.0);
+        assert_that!(result).is_close_to(3_600_000.0, 0.00001);
+    }
+
+    #[test]
+    fn from_calorie_to_joule() {
+        let result = test_conversion(Unit::Calorie, Unit::Joule, 1.0);
+        assert_that!(result).is_close_to(4.184, 0.00001);
+    }
+
+    #[test]
+    fn from_kilowatt_hour_to_calorie() {
+        let result = test_conversion(Unit::KilowattHour, Unit::Calorie, 1.0);
+        assert_that!(result).is_close_to(860_420.65009560, 0.00001);
+    }
+
+    #[test]
+    fn energy_should_not_convert_to_other_dimensions() {
+        let request = ConversionRequest::new(Unit::Joule, Unit::Gram, 1.0);
+        assert_that!(request.execute(&Converter::default())).is_err();
+    }
+
+    #[test]
+    fn from_mole_to_count() {
+        let result = test_conversion(Unit::Mole, Unit::Count, 1.0);
+        assert_that!(result).is_close_to(6.02214076e23, 1e8);
+    }
+
+    #[test]
+    fn from_count_to_mole() {
+        let result = test_conversion(Unit::Count, Unit::Mole, 6.02214076e23);
+        assert_that!(result).is_close_to(1.0, 0.00000001);
+    }
+
+    #[test]
+    fn overflowing_conversion_should_fail() {
+        let result =
+            ConversionRequest::new(Unit::Mole, Unit::Count, 1e300).execute(&Converter::default());
+        assert_that!(matches!(result, Err(ConvertError::NonFiniteResult { .. }))).is_true();
+    }
+
+    #[test]
+    fn from_minus_40_celsius_to_fahrenheit() {
+        let result = test_conversion(Unit::Celsius, Unit::Fahrenheit, -40.0);
+        assert_that!(result).is_equal_to(-40.0);
+    }
+
+    #[test]
+    fn from_minus_40_fahrenheit_to_celsius() {
+        let result = test_conversion(Unit::Fahrenheit, Unit::Celsius, -40.0);
+        assert_that!(result).is_equal_to(-40.0);
+    }
+
+    #[test]
+    fn should_parse_ascii_temperature_symbols() {
+        assert_that!(Unit::try_from("degC").ok()).contains(Unit::Celsius);
+        assert_that!(Unit::try_from("degF").ok()).contains(Unit::Fahrenheit);
+        assert_that!(Unit::SYMBOLS).contains("degC");
+        assert_that!(Unit::SYMBOLS).contains("degF");
+        assert_that!(test_conversion(
+            Unit::try_from("degC").expect("valid unit"),
+            Unit::try_from("degF").expect("valid unit"),
+            100.0
+        ))
+        .is_equal_to(212.0);
+    }
+
+    #[test]
+    fn from_absolute_zero_to_celsius_and_fahrenheit() {
+        assert_that!(test_conversion(Unit::Kelvin, Unit::Celsius, 0.0)).is_equal_to(-273.15);
+        assert_that!(test_conversion(Unit::Kelvin, Unit::Fahrenheit, 0.0)).is_equal_to(-459.67);
+        assert_that!(test_conversion(Unit::Fahrenheit, Unit::Kelvin, -459.67)).is_equal_to(0.0);
+    }
+
+    #[test]
+    fn negative_temperatures_should_round_trip_through_every_scale() {
+        let scales = [Unit::Celsius, Unit::Fahrenheit, Unit::Kelvin];
+        for quantity in [-0.5, -40.0, -100.25, -273.15] {
+            for from in scales {
+                for through in scales {
+                    let there = test_conversion(from, through, quantity);
+                    let back = test_conversion(through, from, there);
+                    let description = format!("{quantity} {from:?} through {through:?}");
+                    asserting!(&description)
+                        .that(&back)
+                        .is_close_to(quantity, 0.0000001);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn from_gauge_to_absolute_pressure() {
+        assert_that!(test_conversion(Unit::PsiGauge, Unit::Psi, 0.0)).is_equal_to(14.696);
+        assert_that!(test_conversion(Unit::Psi, Unit::PsiGauge, 14.696)).is_equal_to(0.0);
+        assert_that!(test_conversion(Unit::PsiGauge, Unit::Psi, 30.0)).is_equal_to(44.696);
+        assert_that!(test_conversion(Unit::PsiGauge, Unit::Pascal, 0.0))
+            .is_close_to(101_325.0, 1.0);
+        assert_that!(test_conversion(Unit::Bar, Unit::PsiGauge, 1.01325)).is_close_to(0.0, 0.001);
+    }
+
+    #[test]
+    fn from_rpm_to_hertz() {
+        assert_that!(test_conversion(Unit::Rpm, Unit::Hertz, 60.0)).is_equal_to(1.0);
+        assert_that!(test_conversion(Unit::Hertz, Unit::Rpm, 1.0)).is_equal_to(60.0);
+        assert_that!(test_conversion(Unit::Rpm, Unit::Hertz, 1.0)).is_equal_to(0.01666667);
+        assert_that!(test_conversion(Unit::Rpm, Unit::Kilohertz, 3000.0)).is_equal_to(0.05);
+    }
+
+    #[test]
+    fn from_hour_to_minute() {
+        assert_that!(test_conversion(Unit::Hour, Unit::Minute, 1.5)).is_equal_to(90.0);
+        assert_that!(test_conversion(Unit::Minute, Unit::Hour, 30.0)).is_equal_to(0.5);
+        assert_that!(test_conversion(Unit::Hour, Unit::Second, 1.0)).is_equal_to(3600.0);
+    }
+
+    #[test]
+    fn from_kilohertz_to_hertz() {
+        assert_that!(test_conversion(Unit::Kilohertz, Unit::Hertz, 1.0)).is_equal_to(1000.0);
+        assert_that!(test_conversion(Unit::Hertz, Unit::Kilohertz, 1.0)).is_equal_to(0.001);
+    }
+
+    #[test]
+    fn zero_should_convert_to_zero() {
+        for from in Unit::ALL {
+            for to in Unit::ALL
+                .into_iter()
+                .filter(|to| to.dimension() == from.dimension())
+            {
+                let result = ConversionRequest::new(from, to, 0.0).execute(&Converter::default());
+                match (from, to) {
+                    (Unit::Mpg, Unit::LPer100Km) | (Unit::LPer100Km, Unit::Mpg) => {
+                        assert_that!(result).is_err();
+                    }
+                    // Temperature scales, and gauge and absolute pressures, have different origins
+                    (from, to)
+                        if from != to
+                            && (from.dimension() == Dimension::Temperature
+                                || from == Unit::PsiGauge
+                                || to == Unit::PsiGauge) =>
+                    {
+                        assert_that!(result).is_ok().is_not_equal_to(0.0);
+                    }
+                    _ => {
+                        assert_that!(result).is_ok().is_equal_to(0.0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reciprocal_rules_should_be_inverted_and_combined() {
+        let mpg_to_l_per_100_km = ConversionRule {
+            from: Unit::Mpg,
+            to: Unit::LPer100Km,
+            factor: 235.2145833,
+            offset: 0.0,
+            kind: RuleKind::Reciprocal,
+            provenance: Provenance::Seeded,
+        };
+
+        let inverted = mpg_to_l_per_100_km.invert();
+        let identity = mpg_to_l_per_100_km.combine(&inverted);
+
+        assert_that!(inverted.kind()).is_equal_to(RuleKind::Reciprocal);
+        assert_that!(inverted.convert(7.84048611)).is_close_to(30.0, 0.000001);
+        assert_that!(identity.kind()).is_equal_to(RuleKind::Affine);
+        assert_that!(identity.convert(30.0)).is_equal_to(30.0);
+    }
+
+    #[test]
+    fn slice_conversion_should_match_scalar_conversion() {
+        let quantities: Vec<f64> = (0..1003)
+            .map(|i| (f64::from(i) - 500.0) * 1.618_033_988_749_895)
+            .collect();
+        let rules = [
+            ConversionRule::try_from(&["kg", "lb", "2.20462262"]).expect("valid rule"),
+            ConversionRule {
+                offset: 32.0,
+                ..ConversionRule::try_from(&["°C", "°F", "1.8"]).expect("valid rule")
+            },
+            ConversionRule {
+                kind: RuleKind::Reciprocal,
+                ..ConversionRule::try_from(&["mpg", "l/100km", "235.214583"]).expect("valid rule")
+            },
+        ];
+
+        for rule in rules {
+            let mut converted = quantities.clone();
+            rule.convert_slice(&mut converted);
+
+            for (quantity, converted) in quantities.iter().zip(converted) {
+                assert_that!(converted.to_bits()).is_equal_to(rule.convert(*quantity).to_bits());
+            }
+        }
+    }
+
+    /// A xorshift64 generator, seeded so failing cases can be replayed.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+            items[self.next() as usize % items.len()]
+        }
+
+        /// A quantity in `[-1000, 1000)`
+        fn quantity(&mut self) -> f64 {
+            (self.next() >> 11) as f64 / (1u64 << 53) as f64 * 2000.0 - 1000.0
+        }
+    }
+
+    #[test]
+    fn combined_rules_should_be_transitive() {
+        let table = ConversionTable::build(&[]);
+        let rule = |from, to| {
+            *table
+                .find(from, to)
+                .expect("every rule should be in the table")
+        };
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..10_000 {
+            let dimension = rng.pick(&Dimension::ALL);
+            let units: Vec<Unit> = Unit::ALL
+                .into_iter()
+                .filter(|unit| unit.dimension() == dimension)
+                .collect();
+            if units.len() < 3 {
+                continue;
+            }
+
+            let a = rng.pick(&units);
+            let b = rng.pick(&units);
+            let c = rng.pick(&units);
+            if a == b || b == c || a == c {
+                continue;
+            }
+
+            let quantity = rng.quantity();
+            let (a_to_c, a_to_b, b_to_c) = (rule(a, c), rule(a, b), rule(b, c));
+            let direct = a_to_c.convert(quantity);
+            let chained = b_to_c.convert(a_to_b.convert(quantity));
+
+            let description = format!(
+                "{quantity} {a:?} -> {c:?} (factor {}, offset {}) through {b:?} \
+                 (factors {} and {}, offsets {} and {})",
+                a_to_c.factor,
+                a_to_c.offset,
+                a_to_b.factor,
+                b_to_c.factor,
+                a_to_b.offset,
+                b_to_c.offset,
+            );
+            let tolerance = 1e-9 * direct.abs().max(chained.abs()).max(1.0);
+            asserting!(&description)
+                .that(&chained)
+                .is_close_to(direct, tolerance);
+        }
+    }
+
+    #[test]
+    fn missing_rule_should_fall_back_to_base_unit() {
+        let rules = [
+            ConversionRule::try_from(&["lb", "g", "453.59237"]).expect("valid rule"),
+            ConversionRule::try_from(&["g", "metric ton", "0.000001"]).expect("valid rule"),
+        ];
+        let converter = Converter {
+            table: Arc::new(ConversionTable {
+                rules: rules.into_iter().collect(),
+            }),
+            ..Converter::default()
+        };
+
+        let result = ConversionRequest::new(Unit::Lb, Unit::Ton, 1000.0)
+            .execute(&converter)
+            .expect("valid conversion");
+
+        assert_that!(result).is_close_to(0.45359237, 0.00000001);
+    }
+
+    #[test]
+    fn every_dimension_should_be_connected_by_known_conversions() {
+        let mut edges: Vec<(Unit, Unit)> = known_rules().map(|rule| (rule.from, rule.to)).collect();
+        // Metric units are all connected through their SI prefix
+        edges.extend(
+            Unit::ALL
+                .into_iter()
+                .filter(Unit::is_metric)
+                .map(|unit| (unit, Unit::Gram)),
+        );
+
+        for unit in Unit::ALL {
+            let base_unit = unit.dimension().base_unit();
+            let mut reached = HashSet::from([base_unit]);
+            let mut to_visit = vec![base_unit];
+            while let Some(current) = to_visit.pop() {
+                for &(from, to) in &edges {
+                    for (a, b) in [(from, to), (to, from)] {
+                        if a == current && reached.insert(b) {
+                            to_visit.push(b);
+                        }
+                    }
+                }
+            }
+
+            let description =
+                format!("{unit:?} is connected to {base_unit:?} by known conversions");
+            asserting!(&description)
+                .that(&reached.contains(&unit))
+                .is_true();
+        }
     }
 }
@@ -0,0 +1,390 @@
+use crate::config::Config;
+use crate::conversion::{ConversionRule, ConversionTable, RuleKind, CONVERSION_TABLE};
+#[cfg(feature = "admin")]
+use crate::error::ConvertError;
+use crate::error::OverridesError;
+#[cfg(feature = "admin")]
+use crate::rules::{self, RuleIssue};
+use rocket::fairing::AdHoc;
+use rocket::figment::providers::{Format, Toml};
+use rocket::figment::Figment;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::serde::json::serde_json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::Request;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Manage the [`SharedConverter`] built from the Rocket configuration.
+pub fn stage() -> AdHoc {
+    AdHoc::try_on_ignite("Converter", |rocket| async {
+        let config: Config = match rocket.figment().extract() {
+            Ok(config) => config,
+            Err(err) => {
+                rocket::config::pretty_print_error(err);
+                return Err(rocket);
+            }
+        };
+
+        match Converter::new(config) {
+            Ok(converter) => Ok(rocket.manage(SharedConverter::new(converter))),
+            Err(err) => {
+                error!("{}", err);
+                Err(rocket)
+            }
+        }
+    })
+}
+
+/// The application settings and the conversion table built from them,
+/// everything needed to execute a conversion.
+#[derive(Debug, Clone)]
+pub struct Converter {
+    pub config: Arc<Config>,
+    pub table: Arc<ConversionTable>,
+    /// The version declared by the overrides file, if any.
+    pub overrides_version: Option<String>,
+    /// The rules read from the overrides file.
+    pub overrides: Arc<Vec<ConversionRule>>,
+    /// The rules added with `POST /rules`, most recent first, taking precedence over
+    /// the overrides.
+    pub custom_rules: Arc<Vec<ConversionRule>>,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Converter {
+            config: Arc::new(Config::default()),
+            table: Arc::new(CONVERSION_TABLE.clone()),
+            overrides_version: None,
+            overrides: Arc::default(),
+            custom_rules: Arc::default(),
+        }
+    }
+}
+
+impl Converter {
+    /// Build the conversion table, applying the rules of the configured overrides file if any.
+    pub fn new(config: Config) -> Result<Self, OverridesError> {
+        let (table, overrides_version, overrides) = match &config.overrides_file {
+            Some(path) => {
+                let overrides = load_overrides(path)?;
+                let table = ConversionTable::build(&overrides.rules);
+                (table, overrides.version, overrides.rules)
+            }
+            None => (CONVERSION_TABLE.clone(), None, vec![]),
+        };
+
+        Ok(Converter {
+            config: Arc::new(config),
+            table: Arc::new(table),
+            overrides_version,
+            overrides: Arc::new(overrides),
+            custom_rules: Arc::default(),
+        })
+    }
+
+    /// Rebuild the conversion table with `custom_rules` taking precedence over the overrides.
+    #[cfg(feature = "admin")]
+    fn with_custom_rules(self, custom_rules: Vec<ConversionRule>) -> Self {
+        let rules: Vec<ConversionRule> = custom_rules
+            .iter()
+            .chain(self.overrides.iter())
+            .copied()
+            .collect();
+
+        Converter {
+            table: Arc::new(ConversionTable::build(&rules)),
+            custom_rules: Arc::new(custom_rules),
+            ..self
+        }
+    }
+}
+
+/// The current [`Converter`], which can be swapped for a rebuilt one at runtime.
+pub struct SharedConverter(RwLock<Converter>);
+
+impl SharedConverter {
+    pub fn new(converter: Converter) -> Self {
+        SharedConverter(RwLock::new(converter))
+    }
+
+    /// A snapshot of the current converter, unaffected by later reloads.
+    pub fn current(&self) -> Converter {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Add a rule to the conversion table, replacing the custom rule between the same units
+    /// if any. Refused once the configured `max_custom_rules` have been added. The rule is
+    /// validated against the table it is added to, and only added when it has no issue other
+    /// than a conflict, the issues found are returned otherwise.
+    #[cfg(feature = "admin")]
+    pub fn add_rule(&self, rule: ConversionRule) -> Result<Vec<RuleIssue>, ConvertError<'static>> {
+        let mut current = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        let issues: Vec<RuleIssue> = rules::validate(&rule, &current.table)
+            .into_iter()
+            .filter(|issue| !matches!(issue, RuleIssue::Conflict { .. }))
+            .collect();
+        if !issues.is_empty() {
+            return Ok(issues);
+        }
+
+        let same_units = |other: &ConversionRule| {
+            (other.from, other.to) == (rule.from, rule.to)
+                || (other.from, other.to) == (rule.to, rule.from)
+        };
+        let custom_rules: Vec<ConversionRule> = std::iter::once(rule)
+            .chain(
+                current
+                    .custom_rules
+                    .iter()
+                    .filter(|other| !same_units(other))
+                    .copied(),
+            )
+            .collect();
+
+        let max = current.config.max_custom_rules;
+        if custom_rules.len() > max {
+            return Err(ConvertError::RuleLimitReached {
+                count: current.custom_rules.len(),
+                max,
+            });
+        }
+
+        *current = current.clone().with_custom_rules(custom_rules);
+        Ok(issues)
+    }
+
+    /// Rebuild the converter from its configuration, reading the overrides file again.
+    /// Custom rules are kept, and so is the current converter if the overrides file is invalid.
+    /// The write lock is held throughout, so that no rule added meanwhile is lost.
+    #[cfg(feature = "admin")]
+    pub fn reload(&self) -> Result<(), OverridesError> {
+        let mut current = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        let converter = Converter::new(Config::clone(&current.config))?
+            .with_custom_rules(current.custom_rules.to_vec());
+        *current = converter;
+        Ok(())
+    }
+}
+
+/// Routes take a snapshot of the current converter, so a request sees the same conversion table
+/// from start to end even when it is reloaded meanwhile.
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Converter {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        request
+            .rocket()
+            .state::<SharedConverter>()
+            .map(SharedConverter::current)
+            .map(Outcome::Success)
+            .unwrap_or(Outcome::Error((Status::InternalServerError, ())))
+    }
+}
+
+/// The content of a conversion overrides file.
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Overrides {
+    /// A free form version of the overridden factors, reported by `GET /factors/version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub rules: Vec<ConversionRule>,
+}
+
+/// Read the overrides from a JSON file, or a TOML file for any other extension.
+fn load_overrides(path: &Path) -> Result<Overrides, OverridesError> {
+    let parse_error = |reason: String| OverridesError::Parse {
+        path: path.to_path_buf(),
+        reason,
+    };
+
+    let content = std::fs::read_to_string(path).map_err(|source| OverridesError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let overrides: Overrides = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&content).map_err(|err| parse_error(err.to_string()))?
+    } else {
+        Figment::from(Toml::string(&content))
+            .extract()
+            .map_err(|err| parse_error(err.to_string()))?
+    };
+
+    for rule in &overrides.rules {
+        if rule.from.dimension() != rule.to.dimension() {
+            return Err(OverridesError::DimensionMismatch {
+                from: rule.from,
+                to: rule.to,
+            });
+        }
+
+        if rule.kind() == RuleKind::Reciprocal && rule.offset() != 0.0 {
+            return Err(OverridesError::ReciprocalOffset {
+                from: rule.from,
+                to: rule.to,
+            });
+        }
+
+        if rule.check_factor().is_err() {
+            return Err(OverridesError::InvalidFactor {
+                from: rule.from,
+                to: rule.to,
+                factor: rule.factor(),
+            });
+        }
+    }
+
+    Ok(overrides)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::Config;
+    #[cfg(feature = "admin")]
+    use crate::conversion::ConversionRule;
+    use crate::converter::Converter;
+    #[cfg(feature = "admin")]
+    use crate::converter::SharedConverter;
+    use crate::error::OverridesError;
+    use crate::rocket;
+    #[cfg(feature = "admin")]
+    use crate::rules::RuleIssue;
+    use crate::{ConversionRequest, ConversionResponse, ConversionResult, Unit};
+    use rocket::local::blocking::Client;
+    #[cfg(feature = "admin")]
+    use rocket::serde::json::serde_json;
+    use speculoos::prelude::*;
+    use std::path::PathBuf;
+
+    fn overrides_file(name: &str, content: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("conversion_api_{}_{name}", std::process::id()));
+        std::fs::write(&path, content).expect("writable overrides file");
+        path
+    }
+
+    #[test]
+    fn should_apply_toml_overrides() {
+        let path = overrides_file(
+            "overrides.toml",
+            r#"
+                [[rules]]
+                from = "lb"
+                to = "kilo"
+                factor = 0.5
+            "#,
+        );
+        let figment = rocket::Config::figment().merge(("overrides_file", &path));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert")
+            .json(&ConversionRequest::new(Unit::Lb, Unit::Kilo, 3.0))
+            .dispatch();
+
+        assert_that!(response
+            .into_json::<ConversionResponse>()
+            .map(|response| response.result))
+        .contains(ConversionResult::Number(1.5));
+    }
+
+    #[test]
+    fn should_apply_json_overrides() {
+        let path = overrides_file(
+            "overrides.json",
+            r#"{"rules": [{"from": "kilo", "to": "lb", "factor": 2.0}]}"#,
+        );
+        let config = Config {
+            overrides_file: Some(path),
+            ..Config::default()
+        };
+
+        let converter = Converter::new(config).expect("valid overrides");
+
+        let result = ConversionRequest::new(Unit::Gram, Unit::Lb, 1000.0).execute(&converter);
+        assert_that!(result).is_ok().is_equal_to(2.0);
+    }
+
+    #[test]
+    fn should_reject_overrides_between_dimensions() {
+        let path = overrides_file(
+            "invalid_overrides.json",
+            r#"{"rules": [{"from": "kilo", "to": "degree", "factor": 2.0}]}"#,
+        );
+        let config = Config {
+            overrides_file: Some(path),
+            ..Config::default()
+        };
+
+        assert_that!(Converter::new(config)).is_err();
+    }
+
+    #[test]
+    fn should_reject_overrides_with_invalid_factor() {
+        for factor in ["0.0", "-2.0"] {
+            let path = overrides_file(
+                &format!("invalid_factor_{factor}.json"),
+                &format!(r#"{{"rules": [{{"from": "kilo", "to": "lb", "factor": {factor}}}]}}"#),
+            );
+            let config = Config {
+                overrides_file: Some(path),
+                ..Config::default()
+            };
+
+            assert_that!(matches!(
+                Converter::new(config),
+                Err(OverridesError::InvalidFactor { .. })
+            ))
+            .is_true();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_refuse_to_add_rule_with_zero_factor() {
+        let converter = SharedConverter::new(Converter::default());
+        let rule: ConversionRule =
+            serde_json::from_str(r#"{"from": "kilo", "to": "lb", "factor": 0.0}"#)
+                .expect("valid rule");
+
+        assert_that!(converter.add_rule(rule))
+            .is_ok()
+            .is_equal_to(vec![RuleIssue::ZeroFactor]);
+        assert_that!(converter.current().custom_rules.len()).is_equal_to(0);
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_keep_rules_added_during_reloads() {
+        let converter = SharedConverter::new(Converter::default());
+        let rules = [
+            r#"{"from": "kilo", "to": "lb", "factor": 2.0}"#,
+            r#"{"from": "bar", "to": "psi", "factor": 14.0}"#,
+            r#"{"from": "joule", "to": "calorie", "factor": 0.25}"#,
+            r#"{"from": "hour", "to": "minute", "factor": 61.0}"#,
+        ];
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..20 {
+                    converter.reload().expect("valid configuration");
+                }
+            });
+            for rule in rules {
+                let rule: ConversionRule = serde_json::from_str(rule).expect("valid rule");
+                let converter = &converter;
+                scope.spawn(move || converter.add_rule(rule));
+            }
+        });
+
+        assert_that!(converter.current().custom_rules.len()).is_equal_to(rules.len());
+    }
+}
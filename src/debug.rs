@@ -0,0 +1,150 @@
+use crate::conversion::{ConversionRule, Provenance, RuleKind};
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::{ConversionRequest, Unit};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Conversions timed when the request does not set `iterations`.
+const DEFAULT_BENCH_ITERATIONS: u32 = 100_000;
+
+/// Conversions past this number are not timed.
+const MAX_BENCH_ITERATIONS: u32 = 10_000_000;
+
+/// A conversion table rule, along with where it comes from.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct TableEntry {
+    pub from: Unit,
+    pub to: Unit,
+    pub factor: f64,
+    pub offset: f64,
+    pub kind: RuleKind,
+    pub provenance: Provenance,
+}
+
+impl From<&ConversionRule> for TableEntry {
+    fn from(rule: &ConversionRule) -> Self {
+        TableEntry {
+            from: rule.from,
+            to: rule.to,
+            factor: rule.factor(),
+            offset: rule.offset(),
+            kind: rule.kind(),
+
+            provenance: rule.provenance(),
+        }
+    }
+}
+
+/// Dump every rule of the conversion table sorted by source then target unit,
+/// only available with the `debug` feature.
+#[get("/debug/table")]
+pub fn table(converter: Converter) -> Json<Vec<TableEntry>> {
+    let mut entries: Vec<TableEntry> = converter.table.rules().map(TableEntry::from).collect();
+    entries.sort_by_key(|entry| (entry.from, entry.to));
+    Json(entries)
+}
+
+/// The average time a conversion took.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct Bench {
+    /// The conversions timed, at most 10 000 000.
+    pub iterations: u32,
+    pub ns_per_op: f64,
+}
+
+/// Time `iterations` conversions of a kilo quantity to pounds with the running configuration,
+/// only available with the `debug` feature.
+#[get("/debug/bench?<iterations>")]
+pub fn bench(
+    iterations: Option<u32>,
+    converter: Converter,
+) -> Result<Json<Bench>, ConvertError<'static>> {
+    let iterations = iterations
+        .unwrap_or(DEFAULT_BENCH_ITERATIONS)
+        .clamp(1, MAX_BENCH_ITERATIONS);
+
+    let start = Instant::now();
+    for i in 0..iterations {
+        let request = ConversionRequest::new(Unit::Kilo, Unit::Lb, f64::from(i));
+        black_box(black_box(&request).execute(&converter)?);
+    }
+    let elapsed = start.elapsed();
+
+    Ok(Json(Bench {
+        iterations,
+        ns_per_op: elapsed.as_nanos() as f64 / f64::from(iterations),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conversion::{expected_rule_count, Provenance};
+    use crate::debug::{Bench, TableEntry};
+    use crate::rocket;
+    use crate::Unit;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_dump_table_with_provenance() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/debug/table").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let table: Vec<TableEntry> = response.into_json().expect("valid json table");
+        assert_that!(table).has_length(expected_rule_count());
+
+        let provenance = |from, to| {
+            table
+                .iter()
+                .find(|entry| entry.from == from && entry.to == to)
+                .map(|entry| entry.provenance)
+        };
+        assert_that!(provenance(Unit::Lb, Unit::Kilo)).contains(Provenance::Seeded);
+        // kg to lb is the exact reciprocal of the seeded lb to kg rule
+        assert_that!(provenance(Unit::Kilo, Unit::Lb)).contains(Provenance::Seeded);
+        assert_that!(provenance(Unit::Degree, Unit::Radian)).contains(Provenance::Seeded);
+
+        assert_that!(provenance(Unit::Gram, Unit::Lb)).contains(Provenance::Derived);
+    }
+
+    #[test]
+    fn should_dump_table_in_stable_order() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let pairs = || {
+            let table: Vec<TableEntry> = client
+                .get("/debug/table")
+                .dispatch()
+                .into_json()
+                .expect("valid json table");
+            table
+                .into_iter()
+                .map(|entry| (entry.from, entry.to))
+                .collect::<Vec<_>>()
+        };
+
+        let first = pairs();
+        assert_that!(first.windows(2).all(|pair| pair[0] < pair[1])).is_true();
+        assert_that!(pairs()).is_equal_to(first);
+    }
+
+    #[test]
+    fn should_time_conversions() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/debug/bench?iterations=100").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let bench: Bench = response.into_json().expect("valid json body");
+        assert_that!(bench.iterations).is_equal_to(100);
+        assert_that!(bench.ns_per_op.is_finite()).is_true();
+        assert_that!(bench.ns_per_op).is_greater_than(0.0);
+    }
+}
@@ -0,0 +1,54 @@
+/// Unit names still accepted but due to be removed, along with the names to use instead.
+const DEPRECATED_NAMES: [(&str, &[&str]); 1] = [("ton", &["metric ton", "short ton"])];
+
+/// A warning naming the deprecated unit names among `names` and their replacements,
+/// `None` if none of them is deprecated.
+pub fn warning(names: &[String]) -> Option<String> {
+    let warnings: Vec<String> = DEPRECATED_NAMES
+        .iter()
+        .filter(|(deprecated, _)| names.iter().any(|name| name == deprecated))
+        .map(|(deprecated, replacements)| {
+            format!(
+                "'{deprecated}' is deprecated, use '{}' instead",
+                replacements.join("' or '")
+            )
+        })
+        .collect();
+
+    (!warnings.is_empty()).then(|| warnings.join(", "))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rocket;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn deprecated_unit_name_should_be_converted_with_a_warning() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |body: &'static str| {
+            let response = client
+                .post("/convert")
+                .header(ContentType::JSON)
+                .body(body)
+                .dispatch();
+            assert_that!(response.status()).is_equal_to(Status::Ok);
+            response.into_json::<Value>().expect("valid json body")
+        };
+
+        let body = convert(r#"{"from":"ton","to":"kilo","quantity":1}"#);
+        assert_that!(body["result"].as_f64()).contains(1000.0);
+        assert_that!(body["deprecation"].as_str())
+            .is_some()
+            .is_equal_to("'ton' is deprecated, use 'metric ton' or 'short ton' instead");
+
+        let body = convert(r#"{"from":"metric ton","to":"kilo","quantity":1}"#);
+        assert_that!(body.get("deprecation")).is_none();
+
+        let body = convert(r#"{"from":{"unit":"ton","system":"metric"},"to":"kilo","quantity":1}"#);
+        assert_that!(body.get("deprecation")).is_none();
+    }
+}
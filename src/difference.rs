@@ -0,0 +1,122 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::{numeric, system, ConversionRequest, Unit};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::State;
+
+/// A quantity and the unit it is expressed in.
+#[derive(Deserialize, Serialize, Copy, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct Measure {
+    #[serde(deserialize_with = "numeric::number_or_string")]
+    pub value: f64,
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub unit: Unit,
+}
+
+/// Compute `a - b`, expressed in `unit` or in the base unit of their dimension if missing.
+#[derive(Deserialize, Serialize, Copy, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct DifferenceRequest {
+    pub a: Measure,
+    pub b: Measure,
+    #[serde(
+        default,
+        deserialize_with = "system::optional_unit_or_qualified",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub unit: Option<Unit>,
+}
+
+/// The signed difference of two quantities, truncated after the 8th decimal digit.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct Difference {
+    pub difference: f64,
+    pub unit: Unit,
+}
+
+/// Subtract two quantities of the same dimension expressed in different units.
+///
+/// Both quantities are converted to the base unit of their dimension, subtracted,
+/// and the difference is converted to the requested unit.
+#[post("/convert/difference", data = "<request>")]
+pub fn difference(
+    request: Json<DifferenceRequest>,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<Difference>, ConvertError<'static>> {
+    let base_unit = request.a.unit.dimension().base_unit();
+    let unit = request.unit.unwrap_or(base_unit);
+    let in_base = |measure: Measure| {
+        ConversionRequest::new(measure.unit, base_unit, measure.value)
+            .execute_with_exact(&converter)
+            .map(|(_, exact)| exact)
+    };
+
+    let difference = in_base(request.a)? - in_base(request.b)?;
+    let difference = ConversionRequest::new(base_unit, unit, difference).execute(&converter)?;
+    quotas.consume(&api_key, 1)?;
+
+    Ok(Json(Difference { difference, unit }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::difference::Difference;
+    use crate::{rocket, Unit};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_subtract_quantities_in_base_unit() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/difference")
+            .json(&json!({"a": {"value": 1, "unit": "kg"}, "b": {"value": 2, "unit": "lb"}}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        assert_that!(response.into_json())
+            .is_some()
+            .is_equal_to(Difference {
+                difference: 92.81526,
+                unit: Unit::Gram,
+            });
+    }
+
+    #[test]
+    fn should_return_signed_difference_in_requested_unit() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/difference")
+            .json(&json!({
+                "a": {"value": 2, "unit": "lb"},
+                "b": {"value": 1, "unit": "kilo"},
+                "unit": "kilo"
+            }))
+            .dispatch();
+
+        let difference: Difference = response.into_json().expect("valid json body");
+        assert_that!(difference.difference).is_close_to(-0.09281526, 0.00000001);
+    }
+
+    #[test]
+    fn should_refuse_quantities_of_different_dimensions() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/difference")
+            .json(&json!({"a": {"value": 1, "unit": "kilo"}, "b": {"value": 1, "unit": "degree"}}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::BadRequest);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::language::Language;
+use crate::{Dimension, Unit};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A dimension, its base unit and every unit measuring it, designated by their symbols
+/// sorted alphabetically, along with the unit names keyed by symbol.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct DimensionInfo {
+    pub dimension: Dimension,
+    pub base_unit: String,
+    pub units: Vec<String>,
+    pub names: BTreeMap<String, String>,
+}
+
+impl DimensionInfo {
+    /// Describe a dimension, naming its units in the given language.
+    pub fn new(dimension: Dimension, language: Language) -> Self {
+        let dimension_units = Unit::ALL
+            .into_iter()
+            .filter(|unit| unit.dimension() == dimension);
+        let names: BTreeMap<String, String> = dimension_units
+            .map(|unit| (unit.to_string(), language.unit_name(unit).to_string()))
+            .collect();
+
+        DimensionInfo {
+            dimension,
+            base_unit: dimension.base_unit().to_string(),
+            units: names.keys().cloned().collect(),
+            names,
+        }
+    }
+}
+
+/// List the supported dimensions, so clients can discover the units they can convert between.
+/// Unit names are displayed in the language negotiated from the `Accept-Language` header.
+#[get("/dimensions")]
+pub fn dimensions(language: Language) -> Json<Vec<DimensionInfo>> {
+    Json(
+        Dimension::ALL
+            .into_iter()
+            .map(|dimension| DimensionInfo::new(dimension, language))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dimensions::DimensionInfo;
+    use crate::{rocket, Dimension};
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_list_dimensions() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/dimensions").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let dimensions: Vec<DimensionInfo> = response.into_json().expect("valid json body");
+        assert_that!(dimensions).has_length(Dimension::ALL.len());
+        let mass = dimensions
+            .iter()
+            .find(|info| info.dimension == Dimension::Mass)
+            .expect("mass dimension");
+        assert_that!(mass.base_unit).is_equal_to("g".to_string());
+        assert_that!(mass.units).contains("kg".to_string());
+        assert_that!(mass.units).does_not_contain("rad".to_string());
+        assert_that!(mass.units.windows(2).all(|pair| pair[0] < pair[1])).is_true();
+    }
+
+    #[test]
+    fn should_name_units_in_requested_language() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let mass_names = |language| {
+            let response = client
+                .get("/dimensions")
+                .header(Header::new("Accept-Language", language))
+                .dispatch();
+            let dimensions: Vec<DimensionInfo> = response.into_json().expect("valid json body");
+            dimensions
+                .into_iter()
+                .find(|info| info.dimension == Dimension::Mass)
+                .expect("mass dimension")
+                .names
+        };
+
+        let french = mass_names("fr-FR, en;q=0.8");
+        assert_that!(french.get("lb")).contains(&"livre".to_string());
+        assert_that!(french.get("g")).contains(&"gramme".to_string());
+
+        let english = mass_names("de");
+        assert_that!(english.get("lb")).contains(&"pound".to_string());
+    }
+}
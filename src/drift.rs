@@ -0,0 +1,110 @@
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::{numeric, system, Unit};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+
+/// Round trips applied when the request does not set `iterations`.
+const DEFAULT_ITERATIONS: u32 = 100;
+
+/// Round trips past this number are not applied.
+const MAX_ITERATIONS: u32 = 1_000_000;
+
+/// Convert `quantity` from `from` to `to` and back, `iterations` times.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct DriftRequest {
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub from: Unit,
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub to: Unit,
+    #[serde(deserialize_with = "numeric::number_or_string")]
+    pub quantity: f64,
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+}
+
+fn default_iterations() -> u32 {
+    DEFAULT_ITERATIONS
+}
+
+/// The quantity after the round trips, and how far it drifted from the initial one.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct Drift {
+    /// The round trips applied, at most 1 000 000.
+    pub iterations: u32,
+    pub quantity: f64,
+    /// `quantity - initial quantity`
+    pub drift: f64,
+    /// `drift / initial quantity`, zero for a zero initial quantity.
+    pub relative_drift: f64,
+}
+
+/// Measure the drift accumulated by converting the quantity back and forth between two units
+/// without truncation, exposing conversion rules which are not exactly the inverse of each
+/// other.
+#[post("/diagnostics/drift", data = "<request>")]
+pub fn drift(
+    request: Json<DriftRequest>,
+    converter: Converter,
+) -> Result<Json<Drift>, ConvertError<'static>> {
+    let iterations = request.iterations.min(MAX_ITERATIONS);
+
+    let mut quantity = request.quantity;
+    for _ in 0..iterations {
+        let there = request.from.convert_to(request.to, quantity, &converter)?;
+        quantity = request.to.convert_to(request.from, there, &converter)?;
+    }
+
+    let drift = quantity - request.quantity;
+    let relative_drift = match request.quantity {
+        0.0 => 0.0,
+        initial => drift / initial,
+    };
+
+    Ok(Json(Drift {
+        iterations,
+        quantity,
+        drift,
+        relative_drift,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::drift::Drift;
+    use crate::rocket;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+
+    fn drift(from: &str, to: &str) -> Drift {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post("/diagnostics/drift")
+            .json(&json!({"from": from, "to": to, "quantity": 1, "iterations": 1000}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        response.into_json().expect("valid json body")
+    }
+
+    #[test]
+    fn metric_round_trips_should_not_drift() {
+        let drift = drift("kilo", "gram");
+
+        assert_that!(drift.iterations).is_equal_to(1000);
+        assert_that!(drift.drift).is_equal_to(0.0);
+    }
+
+    #[test]
+    fn inconsistent_seeds_should_drift() {
+        // The long ton to kg factor and its floating point inverse are not exact inverses
+        let drift = drift("long_ton", "kilo");
+
+        assert_that!(drift.drift).is_not_equal_to(0.0);
+        assert_that!(drift.relative_drift.abs()).is_less_than(1e-5);
+    }
+}
@@ -1,7 +1,314 @@
+use crate::Unit;
+use rocket::http::Status;
+use rocket::response::{self, Responder};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::Request;
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ConvertError<'a> {
-    #[error("Cannot process unit '{0}' use either 'lb', 'g', 'kg', or 'metric ton'")]
-    UnknownUnit(&'a str),
+    #[error("Cannot process unit '{unit}'{}, use one of {}", did_you_mean(.suggestion), known_symbols())]
+    UnknownUnit {
+        unit: &'a str,
+        /// The closest known unit symbol, if any is close enough.
+        suggestion: Option<&'static str>,
+    },
+    #[error("Cannot convert {from:?} to {to:?}, they do not measure the same dimension")]
+    DimensionMismatch { from: Unit, to: Unit },
+    #[error("Conversion from {from:?} to {to:?} is disabled")]
+    ConversionDisabled { from: Unit, to: Unit },
+    #[error("A batch can hold at most {max} conversions, got {got}")]
+    BatchTooLarge { max: usize, got: usize },
+    #[error("The conversion table is not ready yet")]
+    NotReady,
+    #[error("expected application/json or application/msgpack")]
+    UnsupportedMediaType,
+    #[error("Converting {quantity} {from:?} to {to:?} has no finite result")]
+    NonFiniteResult { from: Unit, to: Unit, quantity: f64 },
+    #[error(
+        "Converting the quantity at index {index} from {from:?} to {to:?} has no finite result"
+    )]
+    NonFiniteResultAt { from: Unit, to: Unit, index: usize },
+    #[error("The exact result {exact} cannot be returned without losing precision")]
+    PrecisionLoss { exact: f64 },
+    #[error("Invalid mixed quantity '{quantity}': {reason}")]
+    InvalidMixedQuantity { quantity: String, reason: String },
+    #[error("Unknown or expired session '{0}'")]
+    UnknownSession(String),
+    #[error("The server already holds {max} sessions, retry once some of them have expired")]
+    TooManySessions { max: usize },
+    #[error(
+        "The server already holds {max} idempotent responses, retry once some of them have expired"
+    )]
+    TooManyIdempotentResponses { max: usize },
+    #[error("The quantity has {digits} significant digits, at most {max} are accepted")]
+    InputTooPrecise { digits: usize, max: usize },
+    #[error("{count} custom rules have already been added, at most {max} are accepted")]
+    RuleLimitReached { count: usize, max: usize },
+    #[error("Converting {quantity} {from:?} to {to:?} gives a subnormal result")]
+    SubnormalResult { from: Unit, to: Unit, quantity: f64 },
+    #[error(
+        "Converting the quantity at index {index} from {from:?} to {to:?} gives a subnormal result"
+    )]
+    SubnormalResultAt { from: Unit, to: Unit, index: usize },
+    #[error("The result {result} cannot be written in base {base}, only integer results can be written in bases 2 to 36")]
+    NotRepresentableInBase { result: f64, base: u32 },
+    #[error("The monthly quota of {limit} conversions of this API key is depleted")]
+    QuotaExceeded { limit: u64 },
+    #[error("Invalid rule from {from:?} to {to:?}, its factor {factor} is not a positive number")]
+    InvalidFactor { from: Unit, to: Unit, factor: f64 },
+    #[error("Invalid sweep: {0}")]
+    InvalidSweep(String),
+    #[error("A sweep can hold at most {max} points, got {got}")]
+    TooManyPoints { max: usize, got: f64 },
+    #[error("Invalid quantity '{0}', expected a decimal number")]
+    InvalidQuantity(String),
+    #[error("Converting {from:?} to {to:?} divides by the quantity, it cannot be done with arbitrary precision")]
+    NotLinear { from: Unit, to: Unit },
+    #[error("Cannot convert rates of {from:?} to rates of {to:?}, the units are not proportional")]
+    NotProportional { from: Unit, to: Unit },
+    #[error("Invalid conversion factor '{0}', expected a number")]
+    UnparsableFactor(&'a str),
+    #[error("No conversion rule from {from:?} to {to:?} can be applied")]
+    UnusableRule { from: Unit, to: Unit },
+    #[error("The conversion could not be completed: {0}")]
+    Internal(String),
+    #[error("The idempotency key '{0}' was already used for a different request")]
+    IdempotencyKeyReused(String),
+    #[error("The request with the idempotency key '{0}' is still being processed")]
+    IdempotencyKeyInUse(String),
+}
+
+impl<'a> ConvertError<'a> {
+    /// An unknown unit error, suggesting the closest known unit symbol.
+    pub fn unknown_unit(unit: &'a str) -> Self {
+        ConvertError::UnknownUnit {
+            unit,
+            suggestion: closest_symbol(unit),
+        }
+    }
+
+    /// Whether the same request may succeed if it is sent again later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ConvertError::UnknownUnit { .. }
+            | ConvertError::DimensionMismatch { .. }
+            | ConvertError::ConversionDisabled { .. }
+            | ConvertError::BatchTooLarge { .. }
+            | ConvertError::UnsupportedMediaType
+            | ConvertError::NonFiniteResult { .. }
+            | ConvertError::NonFiniteResultAt { .. }
+            | ConvertError::PrecisionLoss { .. }
+            | ConvertError::InvalidMixedQuantity { .. }
+            | ConvertError::UnknownSession(_)
+            | ConvertError::InputTooPrecise { .. }
+            | ConvertError::RuleLimitReached { .. }
+            | ConvertError::SubnormalResult { .. }
+            | ConvertError::SubnormalResultAt { .. }
+            | ConvertError::NotRepresentableInBase { .. }
+            | ConvertError::InvalidFactor { .. }
+            | ConvertError::InvalidSweep(_)
+            | ConvertError::TooManyPoints { .. }
+            | ConvertError::InvalidQuantity(_)
+            | ConvertError::NotLinear { .. }
+            | ConvertError::NotProportional { .. }
+            | ConvertError::UnparsableFactor(_)
+            | ConvertError::UnusableRule { .. }
+            | ConvertError::Internal(_)
+            | ConvertError::IdempotencyKeyReused(_) => false,
+            ConvertError::NotReady
+            | ConvertError::QuotaExceeded { .. }
+            | ConvertError::IdempotencyKeyInUse(_)
+            | ConvertError::TooManySessions { .. }
+            | ConvertError::TooManyIdempotentResponses { .. } => true,
+        }
+    }
+
+    /// The HTTP status this error is reported with.
+    pub fn status(&self) -> Status {
+        match self {
+            ConvertError::UnknownUnit { .. }
+            | ConvertError::DimensionMismatch { .. }
+            | ConvertError::BatchTooLarge { .. }
+            | ConvertError::NonFiniteResult { .. }
+            | ConvertError::NonFiniteResultAt { .. }
+            | ConvertError::InvalidMixedQuantity { .. }
+            | ConvertError::InvalidSweep(_)
+            | ConvertError::TooManyPoints { .. }
+            | ConvertError::InvalidQuantity(_) => Status::BadRequest,
+            ConvertError::ConversionDisabled { .. } => Status::Forbidden,
+            ConvertError::UnknownSession(_) => Status::NotFound,
+            ConvertError::RuleLimitReached { .. } | ConvertError::IdempotencyKeyInUse(_) => {
+                Status::Conflict
+            }
+            ConvertError::QuotaExceeded { .. } => Status::TooManyRequests,
+            ConvertError::NotReady
+            | ConvertError::TooManySessions { .. }
+            | ConvertError::TooManyIdempotentResponses { .. } => Status::ServiceUnavailable,
+            ConvertError::UnsupportedMediaType => Status::UnsupportedMediaType,
+            ConvertError::PrecisionLoss { .. }
+            | ConvertError::InputTooPrecise { .. }
+            | ConvertError::SubnormalResult { .. }
+            | ConvertError::SubnormalResultAt { .. }
+            | ConvertError::NotRepresentableInBase { .. }
+            | ConvertError::InvalidFactor { .. }
+            | ConvertError::NotLinear { .. }
+            | ConvertError::NotProportional { .. }
+            | ConvertError::IdempotencyKeyReused(_) => Status::UnprocessableEntity,
+            ConvertError::UnparsableFactor(_)
+            | ConvertError::UnusableRule { .. }
+            | ConvertError::Internal(_) => Status::InternalServerError,
+        }
+    }
+}
+
+/// An invalid conversion overrides file.
+#[derive(Error, Debug)]
+pub enum OverridesError {
+    #[error("Cannot read conversion overrides file {path:?}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid conversion overrides file {path:?}: {reason}")]
+    Parse { path: PathBuf, reason: String },
+    #[error("Invalid conversion override from {from:?} to {to:?}, they do not measure the same dimension")]
+    DimensionMismatch { from: Unit, to: Unit },
+    #[error("Invalid conversion override from {from:?} to {to:?}, reciprocal rules cannot have an offset")]
+    ReciprocalOffset { from: Unit, to: Unit },
+    #[error("Invalid conversion override from {from:?} to {to:?}, its factor {factor} is not a positive number")]
+    InvalidFactor { from: Unit, to: Unit, factor: f64 },
+}
+
+/// Unit symbols further than this edit distance from the unknown unit are never suggested.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+fn did_you_mean(suggestion: &Option<&str>) -> String {
+    suggestion
+        .map(|symbol| format!(" (did you mean '{symbol}'?)"))
+        .unwrap_or_default()
+}
+
+fn known_symbols() -> String {
+    Unit::SYMBOLS
+        .iter()
+        .map(|symbol| format!("'{symbol}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn closest_symbol(unit: &str) -> Option<&'static str> {
+    Unit::SYMBOLS
+        .into_iter()
+        .map(|symbol| (symbol, edit_distance(unit, symbol)))
+        .filter(|(_, distance)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(symbol, _)| symbol)
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// The JSON body of an error response.
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ErrorBody {
+    pub error: String,
+    pub retryable: bool,
+}
+
+impl From<&ConvertError<'_>> for ErrorBody {
+    fn from(error: &ConvertError<'_>) -> Self {
+        ErrorBody {
+            error: error.to_string(),
+            retryable: error.is_retryable(),
+        }
+    }
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for ConvertError<'_> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        (self.status(), Json(ErrorBody::from(&self))).respond_to(request)
+    }
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for OverridesError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        let body = ErrorBody {
+            error: self.to_string(),
+            retryable: false,
+        };
+        (Status::InternalServerError, Json(body)).respond_to(request)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::{edit_distance, ConvertError, ErrorBody};
+    use crate::Unit;
+    use rocket::serde::json::serde_json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn unknown_unit_is_not_retryable() {
+        assert_that!(ConvertError::unknown_unit("parsec").is_retryable()).is_false();
+    }
+
+    #[test]
+    fn unknown_unit_should_suggest_closest_symbol() {
+        let error = Unit::try_from("kgs").expect_err("unknown unit");
+        assert_that!(error.to_string()).contains("did you mean 'kg'?");
+    }
+
+    #[test]
+    fn unknown_unit_should_not_suggest_distant_symbol() {
+        let error = ConvertError::unknown_unit("parsec");
+        assert_that!(error.to_string()).does_not_contain("did you mean");
+    }
+
+    #[test]
+    fn edit_distance_between_symbols() {
+        assert_that!(edit_distance("kgs", "kg")).is_equal_to(1);
+        assert_that!(edit_distance("gr", "grad")).is_equal_to(2);
+        assert_that!(edit_distance("µg", "mg")).is_equal_to(1);
+    }
+
+    #[test]
+    fn dimension_mismatch_is_not_retryable() {
+        let error = ConvertError::DimensionMismatch {
+            from: Unit::Degree,
+            to: Unit::Gram,
+        };
+        assert_that!(error.is_retryable()).is_false();
+    }
+
+    #[test]
+    fn not_ready_is_retryable() {
+        assert_that!(ConvertError::NotReady.is_retryable()).is_true();
+    }
+
+    #[test]
+    fn error_body_should_carry_retryable_flag() {
+        let body = serde_json::to_value(ErrorBody::from(&ConvertError::NotReady))
+            .expect("valid json body");
+
+        assert_that!(body["retryable"].as_bool()).contains(true);
+        assert_that!(body["error"].as_str()).contains("The conversion table is not ready yet");
+    }
 }
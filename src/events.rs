@@ -0,0 +1,158 @@
+use crate::api_key::ApiKey;
+use crate::{ConversionRequest, ConversionResponse, ConversionResult, Unit};
+use rocket::fairing::AdHoc;
+use rocket::response::stream::{Event, EventStream};
+use rocket::serde::{Deserialize, Serialize};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::{self, error::RecvError};
+use rocket::{Shutdown, State};
+
+/// Number of events kept for subscribers lagging behind, older events are dropped.
+const CAPACITY: usize = 1024;
+
+/// Manage the [`Events`] channel conversions are published to.
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Conversion events", |rocket| async {
+        rocket.manage(Events(broadcast::channel(CAPACITY).0))
+    })
+}
+
+/// A conversion executed by `POST /convert`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct ConversionEvent {
+    pub from: Unit,
+    pub to: Unit,
+    pub quantity: f64,
+    pub result: ConversionResult,
+}
+
+/// Broadcast the executed conversions to the `GET /events` subscribers, along with the API key
+/// they were executed with.
+pub struct Events(broadcast::Sender<(Option<String>, ConversionEvent)>);
+
+impl Events {
+    /// Publish a conversion, dropped when nobody is subscribed.
+    pub fn publish(
+        &self,
+        api_key: &ApiKey,
+        request: &ConversionRequest,
+        response: &ConversionResponse,
+    ) {
+        let event = ConversionEvent {
+            from: request.from,
+            to: request.to,
+            quantity: request.quantity,
+            result: response.result.clone(),
+        };
+        let _ = self.0.send((api_key.0.clone(), event));
+    }
+}
+
+/// Stream the conversions executed from now on with the API key of the subscriber as
+/// server-sent `conversion` events, until the client disconnects or the server shuts down.
+/// Subscribers lagging too far behind miss the oldest events.
+#[get("/events")]
+pub fn events(api_key: ApiKey, events: &State<Events>, mut shutdown: Shutdown) -> EventStream![] {
+    let mut receiver = events.0.subscribe();
+    EventStream! {
+        loop {
+            let event = select! {
+                event = receiver.recv() => match event {
+                    Ok((key, event)) if key == api_key.0 => event,
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+                _ = &mut shutdown => break,
+            };
+
+            yield Event::json(&event).event("conversion");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api_key::API_KEY_HEADER;
+    use crate::rocket;
+    use rocket::http::{Header, Status};
+    use rocket::local::asynchronous::Client;
+    use rocket::local::asynchronous::LocalResponse;
+    use rocket::serde::json::serde_json::json;
+    use rocket::tokio::io::AsyncReadExt;
+    use rocket::tokio::time::{timeout, Duration};
+    use speculoos::prelude::*;
+
+    /// Read the next event of the stream.
+    async fn next_event(events: &mut LocalResponse<'_>) -> String {
+        let mut received = String::new();
+        let mut chunk = [0; 256];
+        while !received.ends_with("\n\n") {
+            let read = timeout(Duration::from_secs(1), events.read(&mut chunk))
+                .await
+                .expect("event received in time")
+                .expect("readable event stream");
+            received.push_str(std::str::from_utf8(&chunk[..read]).expect("utf-8 event"));
+        }
+        received
+    }
+
+    #[rocket::async_test]
+    async fn should_stream_conversion_events() {
+        let client = Client::untracked(rocket())
+            .await
+            .expect("valid rocket instance");
+        let mut events = client.get("/events").dispatch().await;
+
+        let response = client
+            .post("/convert")
+            .json(&json!({"from": "kilo", "to": "gram", "quantity": 2}))
+            .dispatch()
+            .await;
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+
+        let received = next_event(&mut events).await;
+        assert_that!(received).starts_with("event:conversion\n");
+        assert_that!(received)
+            .contains(r#""from":"kilo","to":"gram","quantity":2.0,"result":2000.0"#);
+    }
+
+    async fn client_with_api_keys() -> Client {
+        let figment = rocket::Config::figment().merge(("api_keys", ["first", "second"]));
+        Client::untracked(rocket().configure(figment))
+            .await
+            .expect("valid rocket instance")
+    }
+
+    #[rocket::async_test]
+    async fn should_refuse_subscribers_without_api_key() {
+        let client = client_with_api_keys().await;
+
+        let response = client.get("/events").dispatch().await;
+
+        assert_that!(response.status()).is_equal_to(Status::Unauthorized);
+    }
+
+    #[rocket::async_test]
+    async fn should_only_stream_conversions_of_the_subscriber_api_key() {
+        let client = client_with_api_keys().await;
+        let mut events = client
+            .get("/events")
+            .header(Header::new(API_KEY_HEADER, "first"))
+            .dispatch()
+            .await;
+        assert_that!(events.status()).is_equal_to(Status::Ok);
+
+        for (api_key, quantity) in [("second", 1), ("first", 2)] {
+            let response = client
+                .post("/convert")
+                .header(Header::new(API_KEY_HEADER, api_key))
+                .json(&json!({"from": "kilo", "to": "gram", "quantity": quantity}))
+                .dispatch()
+                .await;
+            assert_that!(response.status()).is_equal_to(Status::Ok);
+        }
+
+        assert_that!(next_event(&mut events).await).contains(r#""quantity":2.0"#);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::conversion::FACTORS_VERSION;
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::units;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+
+/// The conversion factors definition in use.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct FactorsVersion {
+    /// Version of the built-in conversion factors.
+    pub version: String,
+    /// Whether some factors are overridden by the configured overrides file.
+    pub overridden: bool,
+    /// The version declared by the overrides file, if any.
+    pub overrides_version: Option<String>,
+}
+
+/// Report the version of the conversion factors in use, so clients can pin a known factor set.
+#[get("/factors/version")]
+pub fn version(converter: Converter) -> Json<FactorsVersion> {
+    Json(FactorsVersion {
+        version: FACTORS_VERSION.to_string(),
+        overridden: converter.config.overrides_file.is_some(),
+        overrides_version: converter.overrides_version.clone(),
+    })
+}
+
+/// The order of magnitude of a conversion factor.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct LogFactor {
+    /// The decimal logarithm of the factor converting `from` to `to`.
+    pub log10: f64,
+}
+
+/// Report the decimal logarithm of the factor converting `from` to `to`, both designated by
+/// their name or symbol, without applying it to any quantity.
+#[get("/factor/log?<from>&<to>")]
+pub fn log<'a>(
+    from: &'a str,
+    to: &'a str,
+    converter: Converter,
+) -> Result<Json<LogFactor>, ConvertError<'a>> {
+    let (from, to) = (units::parse(from)?, units::parse(to)?);
+    let factor = from.rule_to(to, &converter)?.factor();
+    if factor <= 0.0 {
+        return Err(ConvertError::InvalidFactor { from, to, factor });
+    }
+
+    Ok(Json(LogFactor {
+        log10: factor.log10(),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::factors::{FactorsVersion, LogFactor};
+    use crate::rocket;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_report_builtin_factors_version() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/factors/version").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        assert_that!(response.into_json())
+            .is_some()
+            .is_equal_to(FactorsVersion {
+                version: "1".to_string(),
+                overridden: false,
+                overrides_version: None,
+            });
+    }
+
+    #[test]
+    fn should_report_overrides_version() {
+        let path = std::env::temp_dir().join(format!(
+            "conversion_api_{}_versioned_overrides.toml",
+            std::process::id()
+        ));
+        let overrides =
+            "version = \"2024.1\"\n[[rules]]\nfrom = \"lb\"\nto = \"kilo\"\nfactor = 0.45";
+        std::fs::write(&path, overrides).expect("writable overrides file");
+        let figment = rocket::Config::figment().merge(("overrides_file", &path));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let response = client.get("/factors/version").dispatch();
+
+        let version: FactorsVersion = response.into_json().expect("valid json body");
+        assert_that!(version.overridden).is_true();
+        assert_that!(version.overrides_version).contains("2024.1".to_string());
+    }
+
+    #[test]
+    fn should_report_log_of_the_factor() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/factor/log?from=gram&to=ton").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: LogFactor = response.into_json().expect("valid json body");
+        assert_that!(body.log10).is_close_to(-6.0, 1e-12);
+    }
+
+    #[test]
+    fn log_factor_should_refuse_units_of_several_dimensions() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/factor/log?from=gram&to=degree").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::BadRequest);
+    }
+}
@@ -0,0 +1,842 @@
+use crate::config::Locale;
+use crate::system::System;
+use crate::ConversionResult;
+use rocket::serde::json::serde_json;
+use rocket::serde::json::serde_json::value::RawValue;
+use rocket::serde::ser::Error;
+use rocket::serde::{Serialize, Serializer};
+
+/// Display options for a conversion result, read from the request query string.
+#[derive(Debug, Default, FromForm)]
+pub struct OutputOptions {
+    /// Return the untruncated result as well.
+    pub debug: bool,
+    /// Return the result as a string.
+    pub as_string: bool,
+    /// Return the result as a string, with its digits grouped by thousands.
+    pub grouping: bool,
+    /// Return the result as a string, separating its decimals with this rather than
+    /// the `locale` separator.
+    pub decimal_sep: Option<String>,
+    /// Return the result as a string, with its digits grouped by thousands separated by this
+    /// rather than the `locale` separator.
+    pub thousands_sep: Option<String>,
+    /// Return the quantity expressed in the base unit of its dimension as well.
+    pub include_base: bool,
+    /// Round the result to the number of significant digits of the requested quantity.
+    pub match_input_precision: bool,
+    /// Fail rather than return a result truncated beyond the floating point precision.
+    pub strict_precision: bool,
+    /// Return the closest integer as well when the result is within `snap_epsilon` of it.
+    pub snap: bool,
+    /// Return the result followed by the symbol of the target unit as well.
+    pub formatted: bool,
+    /// Refuse quantities written with more significant digits.
+    pub max_input_sigfigs: Option<usize>,
+    /// Remove the trailing zeros of string results.
+    pub trim: bool,
+    /// Return the result as a fraction string when it is a simple ratio.
+    pub as_fraction: bool,
+    /// Return the result rounded to the nearest multiple of `1 / denominator` as well,
+    /// both as a number and as a fraction string.
+    pub nearest_fraction: bool,
+    /// The denominator of `nearest_fraction`, [`DEFAULT_DENOMINATOR`] when not given.
+    pub denominator: Option<u32>,
+    /// Return how much larger the result is than the requested quantity as well, in percent.
+    pub as_percent_of_input: bool,
+    /// Return whether the applied rule was derived from other rules rather than seeded or
+    /// overridden as well.
+    pub provenance: bool,
+    /// Return the relative error introduced by floating point arithmetic and truncation as well.
+    pub error_estimate: bool,
+    /// Return the result as a string written in this base, only possible for integer results.
+    pub output_base: Option<u32>,
+    /// Return the result as a string written in this notation.
+    #[field(default = Notation::Fixed)]
+    pub notation: Notation,
+    /// Return the quantity expressed in the preferred unit of this system as well,
+    /// when the target unit is not part of it.
+    pub system: Option<System>,
+    /// Answer `204 No Content` when the conversion leaves the quantity unchanged.
+    pub no_content_on_identity: bool,
+    /// What to return when the units do not measure the same dimension.
+    #[field(default = MismatchPolicy::Error)]
+    pub on_mismatch: MismatchPolicy,
+}
+
+impl OutputOptions {
+    pub fn is_string_output(&self) -> bool {
+        self.as_string
+            || self.is_grouped()
+            || self.decimal_sep.is_some()
+            || self.notation != Notation::Fixed
+    }
+
+    fn is_grouped(&self) -> bool {
+        self.grouping || self.thousands_sep.is_some()
+    }
+}
+
+/// How string results are written.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, FromFormField)]
+pub enum Notation {
+    /// The integer part followed by the decimal digits configured for the dimension,
+    /// 8 by default, e.g. `12300.00000000`.
+    #[default]
+    Fixed,
+    /// A mantissa of at least 1 and less than 1000, with the decimal digits configured for
+    /// the dimension, times a power of ten whose exponent is a multiple of 3, e.g. `12.30000000e3`.
+    Engineering,
+}
+
+/// What a conversion between units of different dimensions returns.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, FromFormField)]
+pub enum MismatchPolicy {
+    /// Fail with a `400 Bad Request` error.
+    #[default]
+    Error,
+    /// Return a `null` result.
+    Null,
+    /// Return a zero result.
+    Zero,
+}
+
+impl MismatchPolicy {
+    /// The result a dimension mismatch is coerced to, `None` when it is an error.
+    pub fn coerced(&self) -> Option<ConversionResult> {
+        match self {
+            MismatchPolicy::Error => None,
+            MismatchPolicy::Null => Some(ConversionResult::Null),
+            MismatchPolicy::Zero => Some(ConversionResult::Number(0.0)),
+        }
+    }
+}
+
+/// How the exact result of a conversion is rounded before being returned.
+///
+/// Closures taking and returning an `f64` are rounding strategies, such as `f64::floor`.
+pub trait RoundingStrategy {
+    fn round(&self, exact: f64) -> f64;
+}
+
+impl<F: Fn(f64) -> f64> RoundingStrategy for F {
+    fn round(&self, exact: f64) -> f64 {
+        self(exact)
+    }
+}
+
+/// The number of decimal digits results are rounded to when no `precision` is configured
+/// for their dimension.
+pub const DEFAULT_DECIMALS: usize = 8;
+
+/// The built-in rounding strategy, keeping 8 decimal digits.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct EightDecimals;
+
+impl RoundingStrategy for EightDecimals {
+    fn round(&self, exact: f64) -> f64 {
+        Decimals(DEFAULT_DECIMALS).round(exact)
+    }
+}
+
+/// A rounding strategy keeping the given number of decimal digits.
+#[derive(Debug, Copy, Clone)]
+pub struct Decimals(pub usize);
+
+impl RoundingStrategy for Decimals {
+    fn round(&self, exact: f64) -> f64 {
+        format!("{:.*}", self.0, exact).parse().unwrap_or(exact)
+    }
+}
+
+/// Write a number result in positional notation, with the digits of [`EightDecimals`] without
+/// their trailing zeros, e.g. `76262976.40762889` and `0.0000016`.
+///
+/// The shortest representation written by default can differ from them: `76262976.4076289`
+/// parses to the same floating point number, and large or small results are written with
+/// an exponent, `1.6e-6`. Results the 8 decimals would change, such as a `min_result` smaller
+/// than them, keep the shortest representation.
+pub fn json_number(result: f64) -> String {
+    let fixed = format!("{:.8}", result);
+    let fixed = fixed.trim_end_matches('0');
+    match fixed.parse::<f64>() {
+        Ok(parsed) if parsed == result && fixed.ends_with('.') => format!("{fixed}0"),
+        Ok(parsed) if parsed == result => fixed.to_string(),
+        _ => serde_json::to_string(&result).unwrap_or_else(|_| "null".to_string()),
+    }
+}
+
+/// Serialize a number result to JSON as written by [`json_number`]. Binary formats, such as
+/// MessagePack, get the number as is.
+pub fn serialize_number<S: Serializer>(result: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    if !serializer.is_human_readable() {
+        return serializer.serialize_f64(*result);
+    }
+
+    RawValue::from_string(json_number(*result))
+        .map_err(S::Error::custom)?
+        .serialize(serializer)
+}
+
+/// Serialize number results to JSON as written by [`json_number`]. Binary formats, such as
+/// MessagePack, get the numbers as is.
+pub fn serialize_numbers<S: Serializer>(results: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+    if !serializer.is_human_readable() {
+        return results.serialize(serializer);
+    }
+
+    let results = results
+        .iter()
+        .map(|result| RawValue::from_string(json_number(*result)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(S::Error::custom)?;
+    results.serialize(serializer)
+}
+
+/// The number of significant digits of a quantity, read from its shortest decimal representation
+/// (`2.5` has 2 significant digits). Trailing zeros are never considered significant,
+/// returns `None` for zero and non finite quantities.
+pub fn significant_digits(quantity: f64) -> Option<usize> {
+    if quantity == 0.0 || !quantity.is_finite() {
+        return None;
+    }
+
+    let digits: String = quantity
+        .abs()
+        .to_string()
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect();
+
+    Some(digits.trim_start_matches('0').trim_end_matches('0').len())
+}
+
+/// The number of significant digits of a quantity as written by the client, such as `"2.50"`
+/// or `1.5e3`. Leading zeros are never considered significant, trailing zeros only after
+/// a decimal point: `2.50` has 3 significant digits but `100` has 1.
+pub fn raw_significant_digits(quantity: &str) -> usize {
+    let mantissa = quantity.trim().split(['e', 'E']).next().unwrap_or_default();
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let digits = digits.trim_start_matches('0');
+
+    if mantissa.contains('.') {
+        digits.len()
+    } else {
+        digits.trim_end_matches('0').len()
+    }
+}
+
+/// Display an integer result in `base`, between 2 and 36, such as `ff` in base 16.
+/// `None` when the result is not an integer exactly represented by a floating point number.
+pub fn format_integer(result: f64, base: u32) -> Option<String> {
+    const MAX_EXACT_INTEGER: f64 = (1_u64 << f64::MANTISSA_DIGITS) as f64;
+    if !(2..=36).contains(&base) || result.fract() != 0.0 || result.abs() > MAX_EXACT_INTEGER {
+        return None;
+    }
+
+    let mut integer = result.abs() as u64;
+    let mut digits = vec![];
+    loop {
+        digits.push(char::from_digit((integer % u64::from(base)) as u32, base)?);
+        integer /= u64::from(base);
+        if integer == 0 {
+            break;
+        }
+    }
+
+    let sign = if result < 0.0 { "-" } else { "" };
+    Some(sign.chars().chain(digits.into_iter().rev()).collect())
+}
+
+/// Largest denominator of the fractions returned by [`as_fraction`].
+const MAX_DENOMINATOR: f64 = 1000.0;
+
+/// Relative difference under which a fraction is considered equal to the result.
+const FRACTION_TOLERANCE: f64 = 1e-9;
+
+/// Display a result as a fraction, such as `1/2`, when it is the ratio of two integers
+/// with a denominator of at most [`MAX_DENOMINATOR`]. The fraction is the first convergent
+/// of the result continued fraction close enough to it.
+pub fn as_fraction(result: f64) -> Option<String> {
+    if !result.is_finite() || result.abs() >= 1e15 {
+        return None;
+    }
+
+    let sign = if result < 0.0 { "-" } else { "" };
+    let value = result.abs();
+
+    // (numerator, denominator) of the two previous convergents
+    let mut previous = (0.0, 1.0);
+    let mut current = (1.0, 0.0);
+    let mut remainder = value;
+    loop {
+        let term = remainder.floor();
+        let next = (term * current.0 + previous.0, term * current.1 + previous.1);
+        if next.1 > MAX_DENOMINATOR {
+            return None;
+        }
+        (previous, current) = (current, next);
+
+        let (numerator, denominator) = current;
+        if (numerator / denominator - value).abs() <= FRACTION_TOLERANCE * value.max(1.0) {
+            return Some(match denominator {
+                1.0 => format!("{sign}{numerator}"),
+                _ => format!("{sign}{numerator}/{denominator}"),
+            });
+        }
+
+        remainder = 1.0 / (remainder - term);
+    }
+}
+
+/// The denominator of the fractions results are rounded to when none is requested,
+/// the usual precision of imperial measures.
+pub const DEFAULT_DENOMINATOR: u32 = 16;
+
+/// Round a result to the nearest multiple of `1 / denominator`, returning the rounded value and
+/// its display as a mixed number in lowest terms, such as `2 3/16`. `None` for a zero
+/// denominator, or a result too large for its numerator to be an exact floating point number.
+pub fn nearest_fraction(result: f64, denominator: u32) -> Option<(f64, String)> {
+    let denominator = u64::from(denominator);
+    let scaled = (result.abs() * denominator as f64).round();
+    if denominator == 0 || !scaled.is_finite() || scaled >= 2_f64.powi(f64::MANTISSA_DIGITS as i32)
+    {
+        return None;
+    }
+
+    let numerator = scaled as u64;
+    let sign = if result < 0.0 && numerator != 0 {
+        -1.0
+    } else {
+        1.0
+    };
+    let value = sign * numerator as f64 / denominator as f64;
+
+    let (whole, numerator) = (numerator / denominator, numerator % denominator);
+    let divisor = gcd(numerator, denominator);
+    let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+    let sign = if sign < 0.0 { "-" } else { "" };
+    let fraction = match (whole, numerator) {
+        (whole, 0) => format!("{sign}{whole}"),
+        (0, numerator) => format!("{sign}{numerator}/{denominator}"),
+        (whole, numerator) => format!("{sign}{whole} {numerator}/{denominator}"),
+    };
+
+    Some((value, fraction))
+}
+
+/// The greatest common divisor of two integers, `b` when `a` is zero.
+fn gcd(a: u64, b: u64) -> u64 {
+    match a {
+        0 => b,
+        a => gcd(b % a, a),
+    }
+}
+
+/// Round a result to the given number of significant digits.
+pub fn round_to_significant_digits(result: f64, digits: usize) -> f64 {
+    format!("{:.*e}", digits.saturating_sub(1), result)
+        .parse()
+        .unwrap_or(result)
+}
+
+/// Display a result as a string with the given number of decimal digits, or without their
+/// trailing zeros if requested, grouping its integer part by thousands with the `locale`
+/// separators if requested. The separators given in the options take precedence over
+/// the `locale` ones.
+pub fn format_result(
+    result: f64,
+    decimals: usize,
+    options: &OutputOptions,
+    locale: Locale,
+) -> String {
+    if options.notation == Notation::Engineering {
+        return format_engineering(result, decimals, options.trim);
+    }
+
+    let formatted = format!("{:.*}", decimals, result);
+    let (integer, decimals) = formatted
+        .split_once('.')
+        .unwrap_or((formatted.as_str(), ""));
+    let decimals = if options.trim {
+        decimals.trim_end_matches('0')
+    } else {
+        decimals
+    };
+
+    let decimal_separator = match (decimals, &options.decimal_sep) {
+        ("", _) => "",
+        (_, Some(separator)) => separator,
+        (_, None) if options.is_grouped() => locale.decimal_separator(),
+        (_, None) => ".",
+    };
+
+    if !options.is_grouped() {
+        return format!("{integer}{decimal_separator}{decimals}");
+    }
+
+    let (sign, digits) = match integer.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", integer),
+    };
+
+    let digits: Vec<char> = digits.chars().collect();
+    let groups: Vec<String> = digits
+        .rchunks(3)
+        .rev()
+        .map(|group| group.iter().collect())
+        .collect();
+
+    let thousands_separator = options
+        .thousands_sep
+        .as_deref()
+        .unwrap_or(locale.thousands_separator());
+    format!(
+        "{sign}{}{decimal_separator}{decimals}",
+        groups.join(thousands_separator)
+    )
+}
+
+/// Display a result in engineering notation, its mantissa having the given number of decimal
+/// digits, without their trailing zeros if requested. Digits are never grouped, the integer
+/// part of the mantissa has at most 3 of them.
+fn format_engineering(result: f64, decimals: usize, trim: bool) -> String {
+    let mantissa = |exponent: i32| format!("{:.*}", decimals, result / 10_f64.powi(exponent));
+    let magnitude = |mantissa: &str| {
+        mantissa
+            .trim_start_matches('-')
+            .parse::<f64>()
+            .unwrap_or(f64::NAN)
+    };
+
+    let mut exponent = match result.abs().log10().floor() {
+        log if log.is_finite() => (log as i32).div_euclid(3) * 3,
+        _ => 0,
+    };
+    // The logarithm and the rounding of the mantissa can both land on the wrong side
+    // of a power of ten
+    if result != 0.0 && magnitude(&mantissa(exponent)) < 1.0 {
+        exponent -= 3;
+    }
+    if magnitude(&mantissa(exponent)) >= 1000.0 {
+        exponent += 3;
+    }
+
+    let mantissa = mantissa(exponent);
+    let mantissa = if trim && mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &mantissa
+    };
+    format!("{mantissa}e{exponent}")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::Locale;
+    use crate::format::{
+        as_fraction, format_engineering, format_integer, format_result, json_number,
+        nearest_fraction, raw_significant_digits, round_to_significant_digits, significant_digits,
+        OutputOptions,
+    };
+    use crate::rocket;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+    use std::collections::HashMap;
+
+    fn grouping() -> OutputOptions {
+        OutputOptions {
+            grouping: true,
+            ..OutputOptions::default()
+        }
+    }
+
+    #[test]
+    fn should_group_thousands() {
+        let formatted = format_result(1_234_567.5, 8, &grouping(), Locale::En);
+        assert_that!(formatted).is_equal_to("1,234,567.50000000".to_string());
+    }
+
+    #[test]
+    fn should_group_negative_thousands_with_locale() {
+        let formatted = format_result(-1_234.5, 8, &grouping(), Locale::De);
+        assert_that!(formatted).is_equal_to("-1.234,50000000".to_string());
+    }
+
+    #[test]
+    fn should_not_group_small_numbers() {
+        let formatted = format_result(123.0, 8, &grouping(), Locale::En);
+        assert_that!(formatted).is_equal_to("123.00000000".to_string());
+    }
+
+    #[test]
+    fn should_trim_trailing_zeros() {
+        let trim = |grouping| OutputOptions {
+            trim: true,
+            grouping,
+            ..OutputOptions::default()
+        };
+
+        assert_that!(format_result(1000.0, 8, &trim(false), Locale::En))
+            .is_equal_to("1000".to_string());
+        assert_that!(format_result(2.5, 8, &trim(false), Locale::En))
+            .is_equal_to("2.5".to_string());
+        assert_that!(format_result(1_234.5, 8, &trim(true), Locale::De))
+            .is_equal_to("1.234,5".to_string());
+        assert_that!(format_result(1000.0, 8, &trim(true), Locale::En))
+            .is_equal_to("1,000".to_string());
+    }
+
+    #[test]
+    fn should_format_integers_in_any_base() {
+        assert_that!(format_integer(255.0, 16)).contains("ff".to_string());
+        assert_that!(format_integer(-5.0, 2)).contains("-101".to_string());
+        assert_that!(format_integer(0.0, 8)).contains("0".to_string());
+        assert_that!(format_integer(2.5, 16)).is_none();
+        assert_that!(format_integer(1e300, 16)).is_none();
+        assert_that!(format_integer(1.0, 1)).is_none();
+    }
+
+    #[test]
+    fn should_convert_in_other_bases() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |query: &str, quantity: &str| {
+            client
+                .post(format!("/convert?{query}"))
+                .header(ContentType::JSON)
+                .body(format!(
+                    r#"{{"from":"kilo","to":"gram","quantity":{quantity}}}"#
+                ))
+                .dispatch()
+        };
+
+        let response = convert("input_base=16&output_base=16", r#""0xff""#);
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        // 255 kilos are 255000 grams
+        assert_that!(body["result"].as_str()).contains("3e418");
+
+        let response = convert("input_base=2", r#""101""#);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_f64()).contains(5000.0);
+
+        let response = convert("output_base=16", "0.0015");
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+
+        let response = convert("input_base=16", r#""fg""#);
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn should_format_engineering_notation() {
+        assert_that!(format_engineering(12_300.0, 8, false))
+            .is_equal_to("12.30000000e3".to_string());
+        assert_that!(format_engineering(-0.00045, 8, true)).is_equal_to("-450e-6".to_string());
+        assert_that!(format_engineering(1000.0, 8, true)).is_equal_to("1e3".to_string());
+        assert_that!(format_engineering(999.9999999999, 8, true)).is_equal_to("1e3".to_string());
+        assert_that!(format_engineering(0.0, 8, true)).is_equal_to("0e0".to_string());
+        assert_that!(format_engineering(12_345.0, 2, false)).is_equal_to("12.35e3".to_string());
+        assert_that!(format_engineering(999.996, 2, false)).is_equal_to("1.00e3".to_string());
+        assert_that!(format_engineering(12_345.0, 0, false)).is_equal_to("12e3".to_string());
+        assert_that!(format_engineering(120_000.0, 0, true)).is_equal_to("120e3".to_string());
+    }
+
+    #[test]
+    fn should_return_engineering_notation_result() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?notation=engineering")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"ton","to":"gram","quantity":12.3}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        let result = body["result"].as_str().expect("string result");
+        let (mantissa, exponent) = result.split_once('e').expect("engineering notation");
+        assert_that!(mantissa).is_equal_to("12.30000000");
+        assert_that!(exponent.parse::<i32>().map(|exponent| exponent % 3)).is_ok_containing(0);
+    }
+
+    #[test]
+    fn should_write_engineering_mantissa_with_the_configured_precision() {
+        let figment = rocket::Config::figment().merge(("precision", HashMap::from([("mass", 2)])));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?notation=engineering")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"ton","to":"gram","quantity":12.3}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_str()).contains("12.30e6");
+    }
+
+    #[test]
+    fn should_find_simple_fractions() {
+        assert_that!(as_fraction(0.5)).contains("1/2".to_string());
+        assert_that!(as_fraction(-1.0 / 3.0)).contains("-1/3".to_string());
+        assert_that!(as_fraction(2.75)).contains("11/4".to_string());
+        assert_that!(as_fraction(1000.0)).contains("1000".to_string());
+        assert_that!(as_fraction(0.0)).contains("0".to_string());
+        assert_that!(as_fraction(std::f64::consts::PI)).is_none();
+        assert_that!(as_fraction(1.0 / 1001.0)).is_none();
+    }
+
+    #[test]
+    fn should_return_fraction_result() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?as_fraction=true")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"gram","to":"kilo","quantity":500}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_str()).contains("1/2");
+    }
+
+    #[test]
+    fn should_estimate_relative_error() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let relative_error = |query: &str| {
+            let response = client
+                .post(format!("/convert?{query}"))
+                .header(ContentType::JSON)
+                .body(r#"{"from":"kilo","to":"lb","quantity":1}"#)
+                .dispatch();
+            let body: Value = response.into_json().expect("valid json body");
+            body.get("relative_error").and_then(Value::as_f64)
+        };
+
+        assert_that!(relative_error("error_estimate=true"))
+            .is_some()
+            .matches(|error| *error > 0.0 && *error < 1e-8);
+        assert_that!(relative_error("")).is_none();
+    }
+
+    #[test]
+    fn should_return_trimmed_string_result() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?as_string=true&trim=true")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"kilo","to":"gram","quantity":1}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_str()).contains("1000");
+    }
+
+    #[test]
+    fn should_return_grouped_string_result() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?grouping=true")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"ton","to":"gram","quantity":1}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_str())
+            .is_some()
+            .starts_with("1,000,000");
+    }
+
+    #[test]
+    fn should_return_string_result_with_requested_separators() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |query: &str| {
+            let response = client
+                .post(format!("/convert?{query}"))
+                .header(ContentType::JSON)
+                .body(r#"{"from":"ton","to":"gram","quantity":1.0000005}"#)
+                .dispatch();
+            assert_that!(response.status()).is_equal_to(Status::Ok);
+            let body: Value = response.into_json().expect("valid json body");
+            body["result"].as_str().map(str::to_string)
+        };
+
+        assert_that!(convert("decimal_sep=,&thousands_sep=."))
+            .is_some()
+            .is_equal_to("1.000.000,50000000".to_string());
+        assert_that!(convert("decimal_sep=,"))
+            .is_some()
+            .is_equal_to("1000000,50000000".to_string());
+        assert_that!(convert("grouping=true&thousands_sep=%20"))
+            .is_some()
+            .is_equal_to("1 000 000.50000000".to_string());
+    }
+
+    #[test]
+    fn json_numbers_should_have_the_truncated_digits() {
+        // Written with 8 decimals, the closest floating point number has more digits
+        // than its shortest representation, 76262976.4076289
+        let result: f64 = "76262976.40762889".parse().expect("valid number");
+        assert_that!(json_number(result)).is_equal_to("76262976.40762889".to_string());
+        assert_that!(json_number(0.0000016)).is_equal_to("0.0000016".to_string());
+        assert_that!(json_number(2000.0)).is_equal_to("2000.0".to_string());
+        assert_that!(json_number(-0.0)).is_equal_to("-0.0".to_string());
+        assert_that!(json_number(1e21)).is_equal_to("1000000000000000000000.0".to_string());
+        assert_that!(json_number(1e-12)).is_equal_to("1e-12".to_string());
+    }
+
+    #[test]
+    fn serialized_result_should_match_string_result() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |query: &str| {
+            let response = client
+                .post(format!("/convert{query}"))
+                .header(ContentType::JSON)
+                .body(r#"{"from":"kilo","to":"milligram","quantity":76.26297640762889}"#)
+                .dispatch();
+            assert_that!(response.status()).is_equal_to(Status::Ok);
+            response.into_string().expect("body")
+        };
+
+        let string = convert("?as_string=true&trim=true");
+        let number = convert("");
+        assert_that!(string).starts_with(r#"{"result":"76262976.4076"#);
+        assert_that!(number).is_equal_to(string.replace('"', "").replace("result", r#""result""#));
+    }
+
+    #[test]
+    fn should_round_to_nearest_fraction() {
+        assert_that!(nearest_fraction(2.20462262, 16)).contains((2.1875, "2 3/16".to_string()));
+        assert_that!(nearest_fraction(0.5, 16)).contains((0.5, "1/2".to_string()));
+        assert_that!(nearest_fraction(-1.97, 16)).contains((-2.0, "-2".to_string()));
+        assert_that!(nearest_fraction(-0.01, 16)).contains((0.0, "0".to_string()));
+        assert_that!(nearest_fraction(0.3, 10)).contains((0.3, "3/10".to_string()));
+        assert_that!(nearest_fraction(1.0, 0)).is_none();
+        assert_that!(nearest_fraction(1e300, 16)).is_none();
+    }
+
+    #[test]
+    fn should_return_nearest_sixteenth() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?nearest_fraction=true")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"kilo","to":"lb","quantity":1}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_f64()).contains(2.20462262);
+        assert_that!(body["fraction_value"].as_f64()).contains(2.1875);
+        assert_that!(body["fraction"].as_str()).contains("2 3/16");
+    }
+
+    #[test]
+    fn should_count_significant_digits() {
+        assert_that!(significant_digits(2.5)).contains(2);
+        assert_that!(significant_digits(0.0025)).contains(2);
+        assert_that!(significant_digits(-120.75)).contains(5);
+        assert_that!(significant_digits(1000.0)).contains(1);
+        assert_that!(significant_digits(0.0)).is_none();
+    }
+
+    #[test]
+    fn should_count_raw_significant_digits() {
+        assert_that!(raw_significant_digits("2.50")).is_equal_to(3);
+        assert_that!(raw_significant_digits("100")).is_equal_to(1);
+        assert_that!(raw_significant_digits("0.0")).is_equal_to(0);
+        assert_that!(raw_significant_digits(" -0.0025 ")).is_equal_to(2);
+        assert_that!(raw_significant_digits("1.25e-3")).is_equal_to(3);
+        assert_that!(raw_significant_digits("12345678901234567891")).is_equal_to(20);
+    }
+
+    #[test]
+    fn should_refuse_too_precise_quantity() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?max_input_sigfigs=6")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"kilo","to":"lb","quantity":1.2345678901234567891}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["error"].as_str())
+            .is_some()
+            .contains("20 significant digits");
+    }
+
+    #[test]
+    fn should_accept_quantity_within_sigfigs_limit() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?max_input_sigfigs=3")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"kilo","to":"gram","quantity":"2.50"}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+    }
+
+    #[test]
+    fn should_round_to_significant_digits() {
+        assert_that!(round_to_significant_digits(5.51155655, 2)).is_equal_to(5.5);
+        assert_that!(round_to_significant_digits(1234.5, 3)).is_equal_to(1230.0);
+        assert_that!(round_to_significant_digits(0.00045359, 1)).is_equal_to(0.0005);
+    }
+
+    #[test]
+    fn should_return_formatted_result() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?formatted=true")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"kilo","to":"lb","quantity":1}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["formatted"].as_str()).contains("2.20462262 lb");
+    }
+
+    #[test]
+    fn should_match_input_precision() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?match_input_precision=true")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"kilo","to":"lb","quantity":2.5}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_f64()).contains(5.5);
+    }
+
+    #[test]
+    fn should_keep_trailing_zeros_of_the_raw_input_precision() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?match_input_precision=true")
+            .header(ContentType::JSON)
+            .body(r#"{"from":"kilo","to":"lb","quantity":"2.50"}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_f64()).contains(5.51);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::conversion::expected_rule_count;
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use rocket::http::Status;
+
+/// Readiness probe: checks the conversion table holds every possible rule,
+/// answering `503 Service Unavailable` otherwise.
+#[get("/ready")]
+pub fn ready(converter: Converter) -> Result<Status, ConvertError<'static>> {
+    if converter.table.len() == expected_rule_count() {
+        Ok(Status::Ok)
+    } else {
+        Err(ConvertError::NotReady)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rocket;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_be_ready_after_startup() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/ready").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+    }
+}
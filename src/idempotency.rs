@@ -0,0 +1,402 @@
+use crate::api_key::ApiKey;
+use crate::error::ConvertError;
+use rocket::fairing::AdHoc;
+use rocket::http::{Header, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::{self, Responder};
+use rocket::{Request, Response};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Header carrying the client provided idempotency key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Header set on responses replayed from the idempotency store.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+/// How long, in seconds, a response is kept when no `idempotency_ttl` is configured.
+const DEFAULT_TTL: u64 = 24 * 60 * 60;
+
+/// How many responses can be held at once when no `idempotency_max_entries` is configured.
+const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Manage an [`IdempotencyStore`] whose entries expire after the configured `idempotency_ttl`,
+/// holding at most `idempotency_max_entries` entries.
+pub fn stage<T: Clone + Send + 'static>() -> AdHoc {
+    AdHoc::on_ignite("Idempotency store", |rocket| async {
+        let ttl = rocket
+            .figment()
+            .extract_inner("idempotency_ttl")
+            .unwrap_or(DEFAULT_TTL);
+        let max_entries = rocket
+            .figment()
+            .extract_inner("idempotency_max_entries")
+            .unwrap_or(DEFAULT_MAX_ENTRIES);
+
+        rocket.manage(IdempotencyStore::<T>::new(
+            Duration::from_secs(ttl),
+            max_entries,
+        ))
+    })
+}
+
+/// The value of the `Idempotency-Key` header, if any.
+pub struct IdempotencyKey(pub String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IdempotencyKey {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.headers().get_one(IDEMPOTENCY_KEY_HEADER) {
+            Some(key) => Outcome::Success(IdempotencyKey(key.to_string())),
+            None => Outcome::Forward(Status::BadRequest),
+        }
+    }
+}
+
+/// An idempotency key, along with the API key it was sent with.
+type ScopedKey = (Option<String>, String);
+
+/// The response to the request identified by `fingerprint`, `None` while it is computed.
+struct Entry<T> {
+    stored_at: Instant,
+    fingerprint: String,
+    response: Option<T>,
+}
+
+/// The entries keyed by their scoped key, along with the keys in insertion order, so that
+/// expired entries are found oldest first without going through every entry.
+struct Entries<T> {
+    entries: HashMap<ScopedKey, Entry<T>>,
+    stored: VecDeque<(Instant, ScopedKey)>,
+}
+
+impl<T> Entries<T> {
+    /// Remove the entries expired at `now`, only visiting the expired ones. A key removed after
+    /// a failure and stored again is queued twice, only its latest insertion removes it.
+    fn remove_expired(&mut self, now: Instant, ttl: Duration) {
+        while let Some((stored_at, key)) = self.stored.front() {
+            if now.duration_since(*stored_at) < ttl {
+                break;
+            }
+            if self
+                .entries
+                .get(key)
+                .is_some_and(|entry| entry.stored_at == *stored_at)
+            {
+                self.entries.remove(key);
+            }
+            self.stored.pop_front();
+        }
+    }
+}
+
+/// Responses of previous requests, keyed by their API key and idempotency key, so that clients
+/// cannot replay each other's responses.
+pub struct IdempotencyStore<T> {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<Entries<T>>,
+}
+
+impl<T: Clone> IdempotencyStore<T> {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        IdempotencyStore {
+            ttl,
+            max_entries,
+            entries: Mutex::new(Entries {
+                entries: HashMap::new(),
+                stored: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Return the response stored for `key`, or compute and store it if there is none.
+    /// Without a key the response is always computed, and failures are never stored.
+    /// A key is refused when it was used for a request with another `fingerprint`, while
+    /// the response of its first request is being computed, or when `max_entries` responses
+    /// have not expired yet.
+    pub fn get_or_execute(
+        &self,
+        key: Option<IdempotencyKey>,
+        api_key: &ApiKey,
+        fingerprint: String,
+        execute: impl FnOnce() -> Result<T, ConvertError<'static>>,
+    ) -> Result<Idempotent<T>, ConvertError<'static>> {
+        let key = match key {
+            Some(IdempotencyKey(key)) => (api_key.0.clone(), key),
+            None => return Ok(Idempotent::new(execute()?, false)),
+        };
+
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            entries.remove_expired(now, self.ttl);
+
+            match entries.entries.get(&key) {
+                Some(entry) if entry.fingerprint != fingerprint => {
+                    return Err(ConvertError::IdempotencyKeyReused(key.1));
+                }
+                Some(Entry {
+                    response: Some(response),
+                    ..
+                }) => return Ok(Idempotent::new(response.clone(), true)),
+                Some(_) => return Err(ConvertError::IdempotencyKeyInUse(key.1)),
+                None if entries.entries.len() >= self.max_entries => {
+                    return Err(ConvertError::TooManyIdempotentResponses {
+                        max: self.max_entries,
+                    });
+                }
+                None => {
+                    let entry = Entry {
+                        stored_at: now,
+                        fingerprint,
+                        response: None,
+                    };
+                    entries.entries.insert(key.clone(), entry);
+                    entries.stored.push_back((now, key.clone()));
+                }
+            }
+        }
+
+        // The store is not locked meanwhile, so that requests with other keys are not delayed
+        let result = execute();
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        match result {
+            Ok(response) => {
+                // Unless the store was cleared meanwhile, as the response may be outdated
+                if let Some(entry) = entries.entries.get_mut(&key) {
+                    entry.response = Some(response.clone());
+                }
+                Ok(Idempotent::new(response, false))
+            }
+            Err(err) => {
+                entries.entries.remove(&key);
+                Err(err)
+            }
+        }
+    }
+
+    /// Forget every stored response, returning how many there were.
+    #[cfg(feature = "admin")]
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let evicted = entries.entries.len();
+        entries.entries.clear();
+        entries.stored.clear();
+        evicted
+    }
+
+    /// The number of stored responses, expired ones included until the next request prunes them.
+    #[cfg(all(test, feature = "admin"))]
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .len()
+    }
+}
+
+/// A response, flagged with `Idempotent-Replayed: true` when it comes from the store.
+pub struct Idempotent<R> {
+    pub response: R,
+    replayed: bool,
+}
+
+impl<R> Idempotent<R> {
+    fn new(response: R, replayed: bool) -> Self {
+        Idempotent { response, replayed }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(R) -> U) -> Idempotent<U> {
+        Idempotent::new(f(self.response), self.replayed)
+    }
+}
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Idempotent<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = Response::build_from(self.response.respond_to(request)?);
+        if self.replayed {
+            response.header(Header::new(IDEMPOTENT_REPLAYED_HEADER, "true"));
+        }
+
+        response.ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        IdempotencyKey, IdempotencyStore, IDEMPOTENCY_KEY_HEADER, IDEMPOTENT_REPLAYED_HEADER,
+    };
+    use crate::api_key::{ApiKey, API_KEY_HEADER};
+    use crate::error::{ConvertError, ErrorBody};
+    use crate::{rocket, ConversionResponse};
+    use rocket::http::{ContentType, Header, Status};
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+    use std::time::Duration;
+
+    const BODY: &str = r#"{"from":"kilo","to":"gram","quantity":2}"#;
+
+    #[test]
+    fn should_replay_response_for_same_key() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let post = || {
+            client
+                .post("/convert")
+                .header(ContentType::JSON)
+                .header(Header::new(IDEMPOTENCY_KEY_HEADER, "some-key"))
+                .body(BODY)
+                .dispatch()
+        };
+
+        let first = post();
+        assert_that!(first.status()).is_equal_to(Status::Ok);
+        assert_that!(first.headers().get_one(IDEMPOTENT_REPLAYED_HEADER)).is_none();
+        let first: Option<ConversionResponse> = first.into_json();
+
+        let second = post();
+        assert_that!(second.status()).is_equal_to(Status::Ok);
+        assert_that!(second.headers().get_one(IDEMPOTENT_REPLAYED_HEADER)).contains("true");
+        assert_that!(second.into_json()).is_equal_to(first);
+    }
+
+    #[test]
+    fn should_execute_requests_without_key() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        client
+            .post("/convert")
+            .header(ContentType::JSON)
+            .body(BODY)
+            .dispatch();
+        let response = client
+            .post("/convert")
+            .header(ContentType::JSON)
+            .body(BODY)
+            .dispatch();
+
+        assert_that!(response.headers().get_one(IDEMPOTENT_REPLAYED_HEADER)).is_none();
+    }
+
+    #[test]
+    fn should_refuse_key_reused_for_another_request() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let post = |body: &'static str| {
+            client
+                .post("/convert")
+                .header(ContentType::JSON)
+                .header(Header::new(IDEMPOTENCY_KEY_HEADER, "some-key"))
+                .body(body)
+                .dispatch()
+        };
+
+        assert_that!(post(BODY).status()).is_equal_to(Status::Ok);
+        let response = post(r#"{"from":"kilo","to":"gram","quantity":3}"#);
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error).contains("already used for a different request");
+
+        let response = client
+            .post("/convert?as_string=true")
+            .header(ContentType::JSON)
+            .header(Header::new(IDEMPOTENCY_KEY_HEADER, "some-key"))
+            .body(BODY)
+            .dispatch();
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn should_not_replay_responses_of_other_api_keys() {
+        let figment = rocket::Config::figment().merge(("api_keys", ["first", "second"]));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let post = |api_key: &'static str| {
+            client
+                .post("/convert")
+                .header(ContentType::JSON)
+                .header(Header::new(API_KEY_HEADER, api_key))
+                .header(Header::new(IDEMPOTENCY_KEY_HEADER, "some-key"))
+                .body(BODY)
+                .dispatch()
+        };
+
+        assert_that!(post("first").headers().get_one(IDEMPOTENT_REPLAYED_HEADER)).is_none();
+        assert_that!(post("second").headers().get_one(IDEMPOTENT_REPLAYED_HEADER)).is_none();
+        assert_that!(post("first").headers().get_one(IDEMPOTENT_REPLAYED_HEADER)).contains("true");
+    }
+
+    #[test]
+    fn should_not_lock_the_store_while_executing() {
+        let store = IdempotencyStore::<u32>::new(Duration::from_secs(60), 10);
+        let key = |key: &str| Some(IdempotencyKey(key.to_string()));
+        let execute = |key, fingerprint: &str, response| {
+            store.get_or_execute(key, &ApiKey(None), fingerprint.to_string(), || Ok(response))
+        };
+
+        let outer = store.get_or_execute(key("outer"), &ApiKey(None), "a".to_string(), || {
+            assert_that!(execute(key("inner"), "b", 2).map(|inner| inner.response))
+                .is_ok_containing(2);
+            assert_that!(matches!(
+                execute(key("outer"), "a", 3),
+                Err(ConvertError::IdempotencyKeyInUse(_))
+            ))
+            .is_true();
+            Ok(1)
+        });
+
+        assert_that!(outer.map(|outer| outer.response)).is_ok_containing(1);
+        assert_that!(execute(key("outer"), "a", 3).map(|outer| outer.response)).is_ok_containing(1);
+    }
+
+    #[test]
+    fn should_refuse_new_keys_once_the_store_is_full() {
+        let store = IdempotencyStore::<u32>::new(Duration::from_secs(60), 2);
+        let execute = |key: &str, response| {
+            store.get_or_execute(
+                Some(IdempotencyKey(key.to_string())),
+                &ApiKey(None),
+                "a".to_string(),
+                || Ok(response),
+            )
+        };
+
+        assert_that!(execute("first", 1).map(|first| first.response)).is_ok_containing(1);
+        assert_that!(execute("second", 2).map(|second| second.response)).is_ok_containing(2);
+
+        let third = execute("third", 3).map(|third| third.response);
+        assert_that!(matches!(
+            third,
+            Err(ConvertError::TooManyIdempotentResponses { max: 2 })
+        ))
+        .is_true();
+        assert_that!(execute("first", 4).map(|first| first.response)).is_ok_containing(1);
+    }
+
+    #[test]
+    fn should_remove_expired_entries_oldest_first() {
+        let store = IdempotencyStore::<u32>::new(Duration::from_millis(50), 2);
+        let execute = |key: &str, response| {
+            store
+                .get_or_execute(
+                    Some(IdempotencyKey(key.to_string())),
+                    &ApiKey(None),
+                    "a".to_string(),
+                    || Ok(response),
+                )
+                .map(|idempotent| idempotent.response)
+        };
+
+        assert_that!(execute("first", 1)).is_ok_containing(1);
+        std::thread::sleep(Duration::from_millis(30));
+        assert_that!(execute("second", 2)).is_ok_containing(2);
+        std::thread::sleep(Duration::from_millis(30));
+
+        assert_that!(execute("third", 3)).is_ok_containing(3);
+        assert_that!(execute("second", 4)).is_ok_containing(2);
+        assert_that!(execute("first", 5).is_err()).is_true();
+    }
+}
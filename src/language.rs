@@ -0,0 +1,148 @@
+use crate::Unit;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use std::convert::Infallible;
+
+/// A language unit names are displayed in, negotiated from the `Accept-Language` header.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Language {
+    #[default]
+    En,
+    Fr,
+}
+
+impl Language {
+    /// The supported language of a language tag, such as `fr` for `fr-CA`.
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split('-').next().unwrap_or_default().trim();
+        match primary.to_ascii_lowercase().as_str() {
+            "en" => Some(Language::En),
+            "fr" => Some(Language::Fr),
+            _ => None,
+        }
+    }
+
+    /// The supported language with the highest quality in an `Accept-Language` header,
+    /// the first one listed among those of equal quality.
+    pub fn negotiate(accept_language: &str) -> Option<Self> {
+        accept_language
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let language = Language::from_tag(parts.next()?)?;
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |quality| quality.parse::<f64>().ok())?;
+                Some((language, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .reduce(|best, other| if other.1 > best.1 { other } else { best })
+            .map(|(language, _)| language)
+    }
+
+    /// The display name of a unit in this language.
+    pub fn unit_name(self, unit: Unit) -> &'static str {
+        match self {
+            Language::En => match unit {
+                Unit::Lb => "pound",
+                Unit::Kilo => "kilogram",
+                Unit::Ton => "metric ton",
+                Unit::ShortTon => "short ton",
+                Unit::LongTon => "long ton",
+                Unit::Stone => "stone",
+                Unit::Ounce => "ounce",
+                Unit::Gram => "gram",
+                Unit::Milligram => "milligram",
+                Unit::Microgram => "microgram",
+                Unit::Degree => "degree",
+                Unit::Radian => "radian",
+                Unit::Gradian => "gradian",
+                Unit::Pascal => "pascal",
+                Unit::Bar => "bar",
+                Unit::Psi => "pound per square inch",
+                Unit::PsiGauge => "pound per square inch gauge",
+                Unit::Mpg => "miles per gallon",
+                Unit::LPer100Km => "litres per 100 kilometres",
+                Unit::Joule => "joule",
+                Unit::Calorie => "calorie",
+                Unit::KilowattHour => "kilowatt-hour",
+                Unit::Mole => "mole",
+                Unit::Count => "count",
+                Unit::Celsius => "degree Celsius",
+                Unit::Fahrenheit => "degree Fahrenheit",
+                Unit::Kelvin => "kelvin",
+                Unit::Hertz => "hertz",
+                Unit::Kilohertz => "kilohertz",
+                Unit::Rpm => "revolution per minute",
+                Unit::Second => "second",
+                Unit::Minute => "minute",
+                Unit::Hour => "hour",
+            },
+            Language::Fr => match unit {
+                Unit::Lb => "livre",
+                Unit::Kilo => "kilogramme",
+                Unit::Ton => "tonne",
+                Unit::ShortTon => "tonne courte",
+                Unit::LongTon => "tonne longue",
+                Unit::Stone => "stone",
+                Unit::Ounce => "once",
+                Unit::Gram => "gramme",
+                Unit::Milligram => "milligramme",
+                Unit::Microgram => "microgramme",
+                Unit::Degree => "degré",
+                Unit::Radian => "radian",
+                Unit::Gradian => "grade",
+                Unit::Pascal => "pascal",
+                Unit::Bar => "bar",
+                Unit::Psi => "livre par pouce carré",
+                Unit::PsiGauge => "livre par pouce carré relative",
+                Unit::Mpg => "miles par gallon",
+                Unit::LPer100Km => "litres aux 100 kilomètres",
+                Unit::Joule => "joule",
+                Unit::Calorie => "calorie",
+                Unit::KilowattHour => "kilowattheure",
+                Unit::Mole => "mole",
+                Unit::Count => "nombre d'entités",
+                Unit::Celsius => "degré Celsius",
+                Unit::Fahrenheit => "degré Fahrenheit",
+                Unit::Kelvin => "kelvin",
+                Unit::Hertz => "hertz",
+                Unit::Kilohertz => "kilohertz",
+                Unit::Rpm => "tour par minute",
+                Unit::Second => "seconde",
+                Unit::Minute => "minute",
+                Unit::Hour => "heure",
+            },
+        }
+    }
+}
+
+/// The language negotiated from the `Accept-Language` header, English when the header is
+/// missing or names no supported language.
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Language {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let language = request
+            .headers()
+            .get_one("Accept-Language")
+            .and_then(Language::negotiate)
+            .unwrap_or_default();
+        Outcome::Success(language)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::language::Language;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_negotiate_supported_language() {
+        assert_that!(Language::negotiate("fr-CA")).contains(Language::Fr);
+        assert_that!(Language::negotiate("de, fr;q=0.5, en;q=0.8")).contains(Language::En);
+        assert_that!(Language::negotiate("en;q=0, FR")).contains(Language::Fr);
+        assert_that!(Language::negotiate("de-DE")).is_none();
+    }
+}
@@ -0,0 +1,34 @@
+use crate::api_key::ApiKey;
+use rocket::http::uri::Origin;
+use rocket::response::Redirect;
+
+/// Permanently redirect the legacy `GET /convert.json` to `GET /convert`, keeping the query
+/// string so old bookmarks keep working.
+#[get("/convert.json")]
+pub fn convert_json(origin: &Origin<'_>, _key: ApiKey) -> Redirect {
+    match origin.query() {
+        Some(query) => Redirect::permanent(format!("/convert?{query}")),
+        None => Redirect::permanent("/convert"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rocket;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_redirect_preserving_the_query_string() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .get("/convert.json?from=kilo&to=gram&quantity=1")
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::PermanentRedirect);
+        assert_that!(response.headers().get_one("Location"))
+            .contains("/convert?from=kilo&to=gram&quantity=1");
+    }
+}
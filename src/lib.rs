@@ -0,0 +1,1602 @@
+#[macro_use]
+extern crate rocket;
+
+use crate::api_key::{ApiKey, Quotas};
+use crate::config::SubnormalPolicy;
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::events::Events;
+use crate::format::{Decimals, OutputOptions};
+pub use crate::format::{EightDecimals, RoundingStrategy};
+use crate::idempotency::{IdempotencyKey, IdempotencyStore, Idempotent};
+use crate::msgpack::NamedMsgPack;
+use crate::numeric::WithRawQuantity;
+use crate::prefix::Prefix;
+use crate::telemetry::RequestSpan;
+use rocket::http::Status;
+use rocket::response::status::NoContent;
+use rocket::serde::{json::Json, msgpack::MsgPack, Deserialize, Serialize};
+use rocket::{Build, Either, Rocket, State};
+use rust_decimal::prelude::{Decimal, ToPrimitive};
+use std::fmt;
+use std::num::FpCategory;
+use unicode_normalization::UnicodeNormalization;
+
+#[cfg(feature = "admin")]
+pub mod admin;
+mod alias;
+pub mod all;
+pub mod api_key;
+pub mod array;
+pub mod assertion;
+pub mod batch;
+#[cfg(feature = "bignum")]
+pub mod bignum;
+pub mod capabilities;
+mod compression;
+mod config;
+mod conversion;
+mod converter;
+#[cfg(feature = "debug")]
+pub mod debug;
+mod deprecation;
+pub mod difference;
+pub mod dimensions;
+pub mod drift;
+pub mod error;
+pub mod events;
+pub mod factors;
+mod format;
+pub mod health;
+mod idempotency;
+mod language;
+pub mod legacy;
+pub mod mixed;
+mod msgpack;
+mod numeric;
+mod prefix;
+pub mod rate;
+pub mod rules;
+pub mod schema;
+pub mod selfcheck;
+pub mod session;
+pub mod stream;
+mod summary;
+pub mod sweep;
+mod system;
+mod telemetry;
+pub mod units;
+
+/// Build the conversion API server.
+pub fn rocket() -> Rocket<Build> {
+    let rocket = rocket::build()
+        .attach(converter::stage())
+        .attach(summary::stage())
+        .attach(idempotency::stage::<ConversionResponse>())
+        .attach(compression::stage())
+        .attach(telemetry::stage())
+        .attach(session::stage())
+        .attach(api_key::stage())
+        .attach(events::stage())
+        .mount(
+            "/",
+            routes![
+                convert_single,
+                convert_msgpack,
+                batch::convert_batch,
+                health::ready,
+                selfcheck::selfcheck,
+                factors::version,
+                factors::log,
+                capabilities::capabilities,
+                dimensions::dimensions,
+                drift::drift,
+                events::events,
+                stream::convert_stream,
+                mixed::convert_mixed,
+                difference::difference,
+                all::convert_all,
+                array::convert_array,
+                sweep::convert_sweep,
+                rate::convert_rate,
+                assertion::convert_assert,
+                rules::validate_rule,
+                rules::rules_diff,
+                rules::export,
+                schema::request,
+                units::reachable,
+                legacy::convert_json,
+                session::create,
+                session::convert,
+                convert_unsupported_media_type
+            ],
+        )
+        .register("/", catchers![unsupported_media_type]);
+
+    #[cfg(feature = "debug")]
+    let rocket = rocket.mount("/", routes![debug::table, debug::bench]);
+
+    #[cfg(feature = "bignum")]
+    let rocket = rocket.mount("/", routes![bignum::convert_big]);
+
+    #[cfg(feature = "admin")]
+    let rocket = rocket.mount(
+        "/",
+        routes![
+            admin::shutdown,
+            admin::reload,
+            admin::clear_cache,
+            rules::add_rule
+        ],
+    );
+
+    rocket
+}
+
+/// Convert the requested quantity, the query string controls how the result is displayed
+/// (see [`OutputOptions`]).
+#[post("/convert?<options..>", format = "json", data = "<conversion>")]
+#[allow(clippy::too_many_arguments)]
+fn convert_single(
+    conversion: WithRawQuantity<ConversionRequest>,
+    options: OutputOptions,
+    key: Option<IdempotencyKey>,
+    store: &State<IdempotencyStore<ConversionResponse>>,
+    converter: Converter,
+    span: &RequestSpan,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+    events: &State<Events>,
+) -> Result<Idempotent<Either<Json<ConversionResponse>, NoContent>>, ConvertError<'static>> {
+    let services = ConvertServices {
+        key,
+        store,
+        converter,
+        span,
+        api_key,
+        quotas,
+        events,
+    };
+
+    convert_request(conversion, &options, services).map(|response| {
+        response.map(|response| {
+            response.map_or(Either::Right(NoContent), |response| {
+                Either::Left(Json(response))
+            })
+        })
+    })
+}
+
+/// Convert the requested quantity like [`convert_single`] does, reading the request and
+/// writing the response as MessagePack rather than JSON. Errors are still reported as JSON.
+#[post("/convert?<options..>", format = "msgpack", data = "<conversion>")]
+#[allow(clippy::too_many_arguments)]
+fn convert_msgpack(
+    conversion: MsgPack<ConversionRequest>,
+    options: OutputOptions,
+    key: Option<IdempotencyKey>,
+    store: &State<IdempotencyStore<ConversionResponse>>,
+    converter: Converter,
+    span: &RequestSpan,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+    events: &State<Events>,
+) -> Result<Idempotent<Either<NamedMsgPack<ConversionResponse>, NoContent>>, ConvertError<'static>>
+{
+    let services = ConvertServices {
+        key,
+        store,
+        converter,
+        span,
+        api_key,
+        quotas,
+        events,
+    };
+    // The raw quantity and unit names are only read from JSON bodies
+    let conversion = WithRawQuantity {
+        value: conversion.into_inner(),
+        raw_quantity: None,
+        unit_names: vec![],
+    };
+
+    convert_request(conversion, &options, services).map(|response| {
+        response.map(|response| {
+            response.map_or(Either::Right(NoContent), |response| {
+                Either::Left(NamedMsgPack(response))
+            })
+        })
+    })
+}
+
+/// The request guards a conversion goes through, whatever the format of its body.
+struct ConvertServices<'r> {
+    key: Option<IdempotencyKey>,
+    store: &'r State<IdempotencyStore<ConversionResponse>>,
+    converter: Converter,
+    span: &'r RequestSpan,
+    api_key: ApiKey,
+    quotas: &'r State<Quotas>,
+    events: &'r State<Events>,
+}
+
+/// Convert the requested quantity, `None` when the response has no content to send.
+fn convert_request(
+    conversion: WithRawQuantity<ConversionRequest>,
+    options: &OutputOptions,
+    services: ConvertServices<'_>,
+) -> Result<Idempotent<Option<ConversionResponse>>, ConvertError<'static>> {
+    let ConvertServices {
+        key,
+        store,
+        converter,
+        span,
+        api_key,
+        quotas,
+        events,
+    } = services;
+    let WithRawQuantity {
+        value: conversion,
+        raw_quantity,
+        unit_names,
+    } = conversion;
+    span.record(&conversion);
+
+    let digits = raw_quantity.as_deref().map(format::raw_significant_digits);
+    if let (Some(digits), Some(max)) = (digits, options.max_input_sigfigs) {
+        if digits > max {
+            return Err(ConvertError::InputTooPrecise { digits, max });
+        }
+    }
+
+    // A key can only be reused for the same body and display options
+    let fingerprint = format!(
+        "{} {raw_quantity:?} {unit_names:?} {options:?}",
+        serde_json::to_string(&conversion).unwrap_or_default()
+    );
+
+    // Only conversions actually executed are charged, not the responses replayed from the store
+    store
+        .get_or_execute(key, &api_key, fingerprint, || {
+            let deprecation = deprecation::warning(&unit_names);
+            let response = ConversionResponse {
+                deprecation,
+                ..ConversionResponse::with_options(
+                    &conversion,
+                    raw_quantity.as_deref(),
+                    options,
+                    &converter,
+                )?
+            };
+            quotas.consume(&api_key, 1)?;
+            events.publish(&api_key, &conversion, &response);
+            Ok(response)
+        })
+        .map(|response| {
+            response.map(|response| {
+                (!(options.no_content_on_identity && response.is_identity(&conversion)))
+                    .then_some(response)
+            })
+        })
+}
+
+/// Conversions posted with another content type than JSON are refused.
+#[post("/convert", rank = 2)]
+fn convert_unsupported_media_type() -> Status {
+    Status::UnsupportedMediaType
+}
+
+#[catch(415)]
+fn unsupported_media_type() -> ConvertError<'static> {
+    ConvertError::UnsupportedMediaType
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ConversionResponse {
+    result: ConversionResult,
+    /// The result before truncation, only returned in debug mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exact: Option<f64>,
+    /// The quantity converted to `base_unit`, only returned when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_value: Option<f64>,
+    /// The base unit of the conversion dimension, only returned when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_unit: Option<Unit>,
+    /// The closest integer, only returned when requested and the result is within
+    /// the configured `snap_epsilon` of it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snapped: Option<f64>,
+    /// The result followed by the symbol of the target unit, such as `2.20462262 lb`,
+    /// only returned when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    formatted: Option<String>,
+    /// The quantity converted to `preferred_unit`, only returned when a system is preferred
+    /// and the target unit is not part of it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preferred_value: Option<f64>,
+    /// The unit of the preferred system for the conversion dimension.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preferred_unit: Option<Unit>,
+    /// The relative error of the result to the conversion computed with decimal arithmetic,
+    /// only returned when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    relative_error: Option<f64>,
+    /// `true` when the result has been clamped to the configured `min_result` or `max_result`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    clamped: Option<bool>,
+    /// The result rounded to the nearest multiple of `1 / denominator`, only returned
+    /// when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fraction_value: Option<f64>,
+    /// `fraction_value` as a mixed number, such as `2 3/16`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fraction: Option<String>,
+    /// How much larger the result is than the requested quantity, in percent, only returned
+    /// when requested and the quantity is not zero.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    percent_of_input: Option<f64>,
+    /// `true` when the applied rule was computed from other rules or from the units SI prefix,
+    /// and may be less precise than a seeded or overridden one. Only returned when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    derived: Option<bool>,
+    /// A warning listing the deprecated unit names of the request and their replacements.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deprecation: Option<String>,
+}
+
+/// A conversion result, either as a number or displayed as a string.
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(crate = "rocket::serde", untagged)]
+pub enum ConversionResult {
+    #[serde(serialize_with = "format::serialize_number")]
+    Number(f64),
+    Text(String),
+    /// No result, for conversions between dimensions coerced by `on_mismatch=null`.
+    Null,
+}
+
+impl ConversionResponse {
+    fn new(
+        request: &ConversionRequest,
+        converter: &Converter,
+    ) -> Result<Self, ConvertError<'static>> {
+        Self::with_options(request, None, &OutputOptions::default(), converter)
+    }
+
+    /// Respond to the request, `raw_quantity` being its quantity as written by the client when
+    /// known, so that `match_input_precision` counts the trailing zeros of `2.50`.
+    fn with_options(
+        request: &ConversionRequest,
+        raw_quantity: Option<&str>,
+        options: &OutputOptions,
+        converter: &Converter,
+    ) -> Result<Self, ConvertError<'static>> {
+        let rule = match (request.rule(converter), options.on_mismatch.coerced()) {
+            (Err(ConvertError::DimensionMismatch { .. }), Some(result)) => {
+                return Ok(Self::plain(result))
+            }
+            (rule, _) => rule?,
+        };
+
+        Self::with_rule(request, raw_quantity, rule, options, converter)
+    }
+
+    /// A response holding nothing but its result, such as the result a dimension mismatch
+    /// is coerced to.
+    pub(crate) fn plain(result: ConversionResult) -> Self {
+        ConversionResponse {
+            result,
+            exact: None,
+            base_value: None,
+            base_unit: None,
+            snapped: None,
+            formatted: None,
+            preferred_value: None,
+            preferred_unit: None,
+            relative_error: None,
+            clamped: None,
+            fraction_value: None,
+            fraction: None,
+            percent_of_input: None,
+            derived: None,
+            deprecation: None,
+        }
+    }
+
+    /// `true` when the request converts a unit to itself, or its result is the requested quantity.
+    fn is_identity(&self, request: &ConversionRequest) -> bool {
+        request.from == request.to || self.result == ConversionResult::Number(request.quantity)
+    }
+
+    /// Respond to the request like [`ConversionResponse::with_options`] does, applying a rule
+    /// already resolved with [`ConversionRequest::rule`].
+    fn with_rule(
+        request: &ConversionRequest,
+        raw_quantity: Option<&str>,
+        rule: conversion::ConversionRule,
+        options: &OutputOptions,
+        converter: &Converter,
+    ) -> Result<Self, ConvertError<'static>> {
+        let decimals = converter.config.decimals(request.to.dimension());
+        let (result, exact) = request.execute_with_rule(rule, converter, &Decimals(decimals))?;
+        if options.strict_precision && (result - exact).abs() > f64::EPSILON * exact.abs() {
+            return Err(ConvertError::PrecisionLoss { exact });
+        }
+
+        let snapped = Some(exact.round()).filter(|integer| {
+            options.snap && (integer - exact).abs() <= converter.config.snap_epsilon
+        });
+
+        let digits = raw_quantity
+            .map(format::raw_significant_digits)
+            .filter(|digits| *digits > 0)
+            .or_else(|| format::significant_digits(request.quantity));
+        let result = match digits {
+            Some(digits) if options.match_input_precision => {
+                format::round_to_significant_digits(result, digits)
+            }
+            _ => result,
+        };
+        let (result, clamped) = converter.config.clamp(result);
+        let formatted = options.formatted.then(|| {
+            format!(
+                "{} {}",
+                format::format_result(result, decimals, options, converter.config.locale),
+                request.to
+            )
+        });
+        let relative_error = options
+            .error_estimate
+            .then(|| request.relative_error(result, converter))
+            .flatten();
+        let derived = options
+            .provenance
+            .then(|| rule.provenance() == conversion::Provenance::Derived);
+        let percent_of_input =
+            (options.as_percent_of_input && request.quantity != 0.0).then(|| {
+                Decimals(decimals).round((result - request.quantity) / request.quantity * 100.0)
+            });
+        let fraction = (options.as_fraction && !clamped)
+            .then(|| format::as_fraction(exact))
+            .flatten();
+        let (fraction_value, nearest_fraction) = options
+            .nearest_fraction
+            .then(|| {
+                let denominator = options.denominator.unwrap_or(format::DEFAULT_DENOMINATOR);
+                format::nearest_fraction(result, denominator)
+            })
+            .flatten()
+            .unzip();
+        let output_base = options.output_base.filter(|base| *base != 10);
+        let result = if let Some(base) = output_base {
+            ConversionResult::Text(
+                format::format_integer(result, base)
+                    .ok_or(ConvertError::NotRepresentableInBase { result, base })?,
+            )
+        } else if let Some(fraction) = fraction {
+            ConversionResult::Text(fraction)
+        } else if options.is_string_output() {
+            ConversionResult::Text(format::format_result(
+                result,
+                decimals,
+                options,
+                converter.config.locale,
+            ))
+        } else {
+            ConversionResult::Number(result)
+        };
+
+        let (base_value, base_unit) = if options.include_base {
+            let base_unit = request.from.dimension().base_unit();
+            let base_value = ConversionRequest::new(request.from, base_unit, request.quantity)
+                .execute(converter)?;
+            (Some(base_value), Some(base_unit))
+        } else {
+            (None, None)
+        };
+
+        let preferred_unit = options
+            .system
+            .filter(|system| !system.contains(request.to))
+            .and_then(|system| system.preferred_unit(request.to.dimension()));
+        let preferred_value = match preferred_unit {
+            Some(unit) => Some(
+                ConversionRequest::new(request.from, unit, request.quantity).execute(converter)?,
+            ),
+            None => None,
+        };
+
+        Ok(ConversionResponse {
+            result,
+            exact: options.debug.then_some(exact),
+            base_value,
+            base_unit,
+            snapped,
+            formatted,
+            preferred_value,
+            preferred_unit,
+            relative_error,
+            clamped: clamped.then_some(true),
+            fraction_value,
+            fraction: nearest_fraction,
+            percent_of_input,
+            derived,
+            deprecation: None,
+        })
+    }
+}
+
+/// Convert `quantity` from one unit to another using the built-in conversion table, returning
+/// the result truncated after the 8th decimal digit.
+///
+/// Unlike the HTTP API, no configuration applies: there are neither overridden factors
+/// nor disabled conversions.
+///
+/// ```
+/// use conversion_api::{convert, Unit};
+///
+/// assert_eq!(convert(Unit::Kilo, Unit::Gram, 1.0).ok(), Some(1000.0));
+/// assert!(convert(Unit::Kilo, Unit::Degree, 1.0).is_err());
+/// ```
+pub fn convert(from: Unit, to: Unit, quantity: f64) -> Result<f64, ConvertError<'static>> {
+    convert_rounded(from, to, quantity, EightDecimals)
+}
+
+/// Convert `quantity` like [`convert`] does, rounding the exact result with the given strategy
+/// rather than keeping 8 decimal digits.
+///
+/// ```
+/// use conversion_api::{convert_rounded, Unit};
+///
+/// assert_eq!(convert_rounded(Unit::Kilo, Unit::Lb, 1.0, f64::floor).ok(), Some(2.0));
+/// ```
+pub fn convert_rounded(
+    from: Unit,
+    to: Unit,
+    quantity: f64,
+    rounding: impl RoundingStrategy,
+) -> Result<f64, ConvertError<'static>> {
+    ConversionRequest::new(from, to, quantity)
+        .execute_with_rounding(&Converter::default(), &rounding)
+        .map(|(result, _)| result)
+}
+
+/// Represent a conversion command, from the given unit to the given unit
+/// with the provided quantity.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ConversionRequest {
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    from: Unit,
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    to: Unit,
+    #[serde(deserialize_with = "numeric::number_or_string")]
+    quantity: f64,
+    /// Convert through this unit rather than directly to the target unit.
+    #[serde(
+        default,
+        deserialize_with = "system::optional_unit_or_qualified",
+        skip_serializing_if = "Option::is_none"
+    )]
+    through: Option<Unit>,
+}
+
+impl ConversionRequest {
+    pub fn new(from: Unit, to: Unit, quantity: f64) -> Self {
+        ConversionRequest {
+            from,
+            to,
+            quantity,
+            through: None,
+        }
+    }
+
+    /// Convert through the given unit instead of applying the direct conversion rule.
+    pub fn through(self, through: Unit) -> Self {
+        ConversionRequest {
+            through: Some(through),
+            ..self
+        }
+    }
+
+    /// Execute the given conversion, returning the conversion result truncated after the
+    /// configured number of decimal digits of the target dimension, 8 by default.
+    pub fn execute(&self, converter: &Converter) -> Result<f64, ConvertError<'static>> {
+        self.execute_with_exact(converter).map(|(result, _)| result)
+    }
+
+    /// Execute the given conversion, returning both the truncated and the exact conversion result.
+    pub fn execute_with_exact(
+        &self,
+        converter: &Converter,
+    ) -> Result<(f64, f64), ConvertError<'static>> {
+        let decimals = converter.config.decimals(self.to.dimension());
+        self.execute_with_rounding(converter, &Decimals(decimals))
+    }
+
+    /// Execute the given conversion, returning both the result rounded by `rounding`
+    /// and the exact conversion result.
+    pub fn execute_with_rounding(
+        &self,
+        converter: &Converter,
+        rounding: &dyn RoundingStrategy,
+    ) -> Result<(f64, f64), ConvertError<'static>> {
+        self.execute_with_rule(self.rule(converter)?, converter, rounding)
+    }
+
+    /// The rule converting the requested units, through the intermediate unit if there is one,
+    /// unless the conversion is impossible or disabled.
+    fn rule(
+        &self,
+        converter: &Converter,
+    ) -> Result<conversion::ConversionRule, ConvertError<'static>> {
+        match self.through {
+            Some(through) => self.from.rule_through(through, self.to, converter),
+            None => self.from.rule_to(self.to, converter),
+        }
+    }
+
+    /// Execute the given conversion with a rule resolved by [`ConversionRequest::rule`].
+    fn execute_with_rule(
+        &self,
+        rule: conversion::ConversionRule,
+        converter: &Converter,
+        rounding: &dyn RoundingStrategy,
+    ) -> Result<(f64, f64), ConvertError<'static>> {
+        let exact = rule.convert(self.quantity);
+
+        // A reciprocal conversion of zero, such as 0 mpg to l/100km, has no result
+        if !exact.is_finite() {
+            return Err(ConvertError::NonFiniteResult {
+                from: self.from,
+                to: self.to,
+                quantity: self.quantity,
+            });
+        }
+
+        let exact = match (exact.classify(), converter.config.subnormal_results) {
+            (FpCategory::Subnormal, SubnormalPolicy::Flush) => {
+                warn!(
+                    "Flushing the subnormal result {} of {} {:?} to {:?} to zero",
+                    exact, self.quantity, self.from, self.to
+                );
+                0.0_f64.copysign(exact)
+            }
+            (FpCategory::Subnormal, SubnormalPolicy::Reject) => {
+                return Err(ConvertError::SubnormalResult {
+                    from: self.from,
+                    to: self.to,
+                    quantity: self.quantity,
+                });
+            }
+            _ => exact,
+        };
+
+        Ok((rounding.round(exact), exact))
+    }
+
+    /// The relative error of `result` to the conversion computed with decimal arithmetic from
+    /// the exact values of the quantity and of the rule factor and offset, `None` when they
+    /// do not fit in a decimal.
+    fn relative_error(&self, result: f64, converter: &Converter) -> Option<f64> {
+        let rule = match self.through {
+            Some(through) => converter.table.find_through(self.from, through, self.to),
+            None => converter.table.find_or_through_base(self.from, self.to),
+        }?;
+        let exact = rule.convert_exactly(self.quantity)?;
+        let error = (Decimal::from_f64_retain(result)? - exact).abs();
+
+        if exact.is_zero() {
+            return error.is_zero().then_some(0.0);
+        }
+        (error / exact.abs()).to_f64()
+    }
+}
+
+/// A physical dimension, conversions are only possible between units of the same dimension.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum Dimension {
+    Mass,
+    Angle,
+    Pressure,
+    FuelEconomy,
+    Energy,
+    /// Amount of substance.
+    Substance,
+    Temperature,
+    Frequency,
+    Time,
+}
+
+impl Dimension {
+    /// Every known dimension.
+    pub const ALL: [Dimension; 9] = [
+        Dimension::Mass,
+        Dimension::Angle,
+        Dimension::Pressure,
+        Dimension::FuelEconomy,
+        Dimension::Energy,
+        Dimension::Substance,
+        Dimension::Temperature,
+        Dimension::Frequency,
+        Dimension::Time,
+    ];
+
+    /// The unit results are normalized to, the SI unit of the dimension (the gram for masses).
+    pub fn base_unit(&self) -> Unit {
+        match self {
+            Dimension::Mass => Unit::Gram,
+            Dimension::Angle => Unit::Radian,
+            Dimension::Pressure => Unit::Pascal,
+            Dimension::FuelEconomy => Unit::LPer100Km,
+            Dimension::Energy => Unit::Joule,
+            Dimension::Substance => Unit::Mole,
+            Dimension::Temperature => Unit::Kelvin,
+            Dimension::Frequency => Unit::Hertz,
+            Dimension::Time => Unit::Second,
+        }
+    }
+}
+
+/// A unit of either weight, metric (microgram, milligram, gram, kilo, ton), pound,
+/// stone, ounce or short and long tons,
+/// angle (degree, radian, gradian), pressure (pascal, bar, absolute and gauge psi)
+/// fuel economy (miles per US gallon, liters per 100 kilometers)
+/// energy (joule, calorie, kilowatt-hour), amount of substance (mole, count of entities)
+/// temperature (degree Celsius, degree Fahrenheit, kelvin)
+/// frequency (hertz, kilohertz, revolutions per minute) or time (second, minute, hour).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum Unit {
+    Lb,
+    Kilo,
+    Ton,
+    #[serde(rename = "short_ton")]
+    ShortTon,
+    #[serde(rename = "long_ton")]
+    LongTon,
+    Stone,
+    Ounce,
+    Gram,
+    Milligram,
+    Microgram,
+    Degree,
+    Radian,
+    Gradian,
+    Pascal,
+    Bar,
+    /// Absolute pressure, in pounds per square inch.
+    Psi,
+    /// Pressure relative to the standard atmosphere, in pounds per square inch.
+    PsiGauge,
+    Mpg,
+    #[serde(rename = "l/100km")]
+    LPer100Km,
+    Joule,
+    Calorie,
+    KilowattHour,
+    Mole,
+    /// A number of elementary entities, such as atoms or molecules.
+    Count,
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+    Hertz,
+    Kilohertz,
+    /// Revolutions per minute.
+    Rpm,
+    Second,
+    Minute,
+    Hour,
+}
+
+impl<'a> TryFrom<&'a str> for Unit {
+    type Error = ConvertError<'a>;
+
+    fn try_from(unit: &'a str) -> Result<Self, ConvertError<'a>> {
+        // Unicode symbols can be written in several equivalent ways, normalize them before matching
+        let symbol: String = unit.nfc().collect();
+        match symbol.as_str() {
+            "lb" => Ok(Unit::Lb),
+            "metric ton" => Ok(Unit::Ton),
+            "short ton" => Ok(Unit::ShortTon),
+            "long ton" => Ok(Unit::LongTon),
+            "st" => Ok(Unit::Stone),
+            "oz" => Ok(Unit::Ounce),
+            "°" | "deg" => Ok(Unit::Degree),
+            "rad" => Ok(Unit::Radian),
+            "grad" => Ok(Unit::Gradian),
+            "Pa" => Ok(Unit::Pascal),
+            "bar" => Ok(Unit::Bar),
+            "psi" | "psia" => Ok(Unit::Psi),
+            "psig" => Ok(Unit::PsiGauge),
+            "mpg" => Ok(Unit::Mpg),
+            "L/100km" | "l/100km" => Ok(Unit::LPer100Km),
+            "J" => Ok(Unit::Joule),
+            "cal" => Ok(Unit::Calorie),
+            "kWh" => Ok(Unit::KilowattHour),
+            "mol" => Ok(Unit::Mole),
+            "count" => Ok(Unit::Count),
+            "°C" | "degC" => Ok(Unit::Celsius),
+            "°F" | "degF" => Ok(Unit::Fahrenheit),
+            "K" => Ok(Unit::Kelvin),
+            "Hz" => Ok(Unit::Hertz),
+            "kHz" => Ok(Unit::Kilohertz),
+            "rpm" => Ok(Unit::Rpm),
+            "s" => Ok(Unit::Second),
+            "min" => Ok(Unit::Minute),
+            "h" => Ok(Unit::Hour),
+            symbol => symbol
+                .strip_suffix('g')
+                .and_then(Prefix::from_symbol)
+                .and_then(Unit::from_gram_prefix)
+                .ok_or_else(|| ConvertError::unknown_unit(unit)),
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl Unit {
+    /// Every known unit.
+    pub const ALL: [Unit; 33] = [
+        Unit::Lb,
+        Unit::Kilo,
+        Unit::Ton,
+        Unit::ShortTon,
+        Unit::LongTon,
+        Unit::Stone,
+        Unit::Ounce,
+        Unit::Gram,
+        Unit::Milligram,
+        Unit::Microgram,
+        Unit::Degree,
+        Unit::Radian,
+        Unit::Gradian,
+        Unit::Pascal,
+        Unit::Bar,
+        Unit::Psi,
+        Unit::PsiGauge,
+        Unit::Mpg,
+        Unit::LPer100Km,
+        Unit::Joule,
+        Unit::Calorie,
+        Unit::KilowattHour,
+        Unit::Mole,
+        Unit::Count,
+        Unit::Celsius,
+        Unit::Fahrenheit,
+        Unit::Kelvin,
+        Unit::Hertz,
+        Unit::Kilohertz,
+        Unit::Rpm,
+        Unit::Second,
+        Unit::Minute,
+        Unit::Hour,
+    ];
+
+    /// Every unit symbol accepted when parsing a unit.
+    pub const SYMBOLS: [&'static str; 38] = [
+        "lb",
+        "µg",
+        "mg",
+        "g",
+        "kg",
+        "Mg",
+        "metric ton",
+        "short ton",
+        "long ton",
+        "st",
+        "oz",
+        "°",
+        "deg",
+        "rad",
+        "grad",
+        "Pa",
+        "bar",
+        "psi",
+        "psia",
+        "psig",
+        "mpg",
+        "L/100km",
+        "J",
+        "cal",
+        "kWh",
+        "mol",
+        "count",
+        "°C",
+        "degC",
+        "°F",
+        "degF",
+        "K",
+        "Hz",
+        "kHz",
+        "rpm",
+        "s",
+        "min",
+        "h",
+    ];
+
+    /// The canonical symbol of this unit, one of [`Unit::SYMBOLS`].
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Lb => "lb",
+            Unit::Kilo => "kg",
+            Unit::Ton => "metric ton",
+            Unit::ShortTon => "short ton",
+            Unit::LongTon => "long ton",
+            Unit::Stone => "st",
+            Unit::Ounce => "oz",
+            Unit::Gram => "g",
+            Unit::Milligram => "mg",
+            Unit::Microgram => "µg",
+            Unit::Degree => "°",
+            Unit::Radian => "rad",
+            Unit::Gradian => "grad",
+            Unit::Pascal => "Pa",
+            Unit::Bar => "bar",
+            Unit::Psi => "psi",
+            Unit::PsiGauge => "psig",
+            Unit::Mpg => "mpg",
+            Unit::LPer100Km => "L/100km",
+            Unit::Joule => "J",
+            Unit::Calorie => "cal",
+            Unit::KilowattHour => "kWh",
+            Unit::Mole => "mol",
+            Unit::Count => "count",
+            Unit::Celsius => "°C",
+            Unit::Fahrenheit => "°F",
+            Unit::Kelvin => "K",
+            Unit::Hertz => "Hz",
+            Unit::Kilohertz => "kHz",
+            Unit::Rpm => "rpm",
+            Unit::Second => "s",
+            Unit::Minute => "min",
+            Unit::Hour => "h",
+        }
+    }
+
+    /// The dimension measured by this unit.
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Lb
+            | Unit::Kilo
+            | Unit::Ton
+            | Unit::ShortTon
+            | Unit::LongTon
+            | Unit::Stone
+            | Unit::Ounce
+            | Unit::Gram
+            | Unit::Milligram
+            | Unit::Microgram => Dimension::Mass,
+            Unit::Degree | Unit::Radian | Unit::Gradian => Dimension::Angle,
+            Unit::Pascal | Unit::Bar | Unit::Psi | Unit::PsiGauge => Dimension::Pressure,
+            Unit::Mpg | Unit::LPer100Km => Dimension::FuelEconomy,
+            Unit::Joule | Unit::Calorie | Unit::KilowattHour => Dimension::Energy,
+            Unit::Mole | Unit::Count => Dimension::Substance,
+            Unit::Celsius | Unit::Fahrenheit | Unit::Kelvin => Dimension::Temperature,
+            Unit::Hertz | Unit::Kilohertz | Unit::Rpm => Dimension::Frequency,
+            Unit::Second | Unit::Minute | Unit::Hour => Dimension::Time,
+        }
+    }
+
+    fn is_metric(&self) -> bool {
+        self.prefix().is_some()
+    }
+
+    /// The SI prefix of a metric unit relative to the gram, `None` for non metric units.
+    fn prefix(&self) -> Option<Prefix> {
+        match self {
+            Unit::Microgram => Some(Prefix::Micro),
+            Unit::Milligram => Some(Prefix::Milli),
+            Unit::Gram => Some(Prefix::None),
+            Unit::Kilo => Some(Prefix::Kilo),
+            Unit::Ton => Some(Prefix::Mega),
+            Unit::Lb
+            | Unit::ShortTon
+            | Unit::LongTon
+            | Unit::Stone
+            | Unit::Ounce
+            | Unit::Degree
+            | Unit::Radian
+            | Unit::Gradian
+            | Unit::Pascal
+            | Unit::Bar
+            | Unit::Psi
+            | Unit::PsiGauge
+            | Unit::Mpg
+            | Unit::LPer100Km
+            | Unit::Joule
+            | Unit::Calorie
+            | Unit::KilowattHour
+            | Unit::Mole
+            | Unit::Count
+            | Unit::Celsius
+            | Unit::Fahrenheit
+            | Unit::Kelvin
+            | Unit::Hertz
+            | Unit::Kilohertz
+            | Unit::Rpm
+            | Unit::Second
+            | Unit::Minute
+            | Unit::Hour => None,
+        }
+    }
+
+    fn from_gram_prefix(prefix: Prefix) -> Option<Unit> {
+        Unit::ALL
+            .into_iter()
+            .find(|unit| unit.prefix() == Some(prefix))
+    }
+
+    fn convert_to(
+        self,
+        to: Unit,
+        quantity: f64,
+        converter: &Converter,
+    ) -> Result<f64, ConvertError<'static>> {
+        Ok(self.rule_to(to, converter)?.convert(quantity))
+    }
+
+    /// The rule converting to the target unit, unless the conversion is impossible or disabled.
+    fn rule_to(
+        self,
+        to: Unit,
+        converter: &Converter,
+    ) -> Result<conversion::ConversionRule, ConvertError<'static>> {
+        if self.dimension() != to.dimension() {
+            return Err(ConvertError::DimensionMismatch { from: self, to });
+        }
+
+        if converter.config.is_disabled(self, to) {
+            return Err(ConvertError::ConversionDisabled { from: self, to });
+        }
+
+        converter
+            .table
+            .find_or_through_base(self, to)
+            .ok_or(ConvertError::UnusableRule { from: self, to })
+    }
+
+    /// The rule converting to `through` then to the target unit, combining both conversion rules.
+    fn rule_through(
+        self,
+        through: Unit,
+        to: Unit,
+        converter: &Converter,
+    ) -> Result<conversion::ConversionRule, ConvertError<'static>> {
+        for (from, to) in [(self, to), (self, through)] {
+            if from.dimension() != to.dimension() {
+                return Err(ConvertError::DimensionMismatch { from, to });
+            }
+        }
+
+        if converter.config.is_disabled(self, to) {
+            return Err(ConvertError::ConversionDisabled { from: self, to });
+        }
+
+        converter
+            .table
+            .find_through(self, through, to)
+            .ok_or(ConvertError::UnusableRule { from: self, to })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::rocket;
+    use crate::config::{Config, SubnormalPolicy, UnitPair};
+    use crate::converter::Converter;
+    use crate::error::ErrorBody;
+    use crate::{
+        convert, convert_rounded, ConversionRequest, ConversionResponse, ConversionResult, Unit,
+    };
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+    use rocket::serde::msgpack;
+    use speculoos::assert_that;
+
+    use speculoos::prelude::*;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn conversion_should_works() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let request = ConversionRequest::new(Unit::Gram, Unit::Kilo, 1000.0);
+
+        let response = client.post("/convert").json(&request).dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        assert_that!(response.into_json())
+            .is_some()
+            .is_equal_to(ConversionResponse::plain(ConversionResult::Number(1.0)));
+    }
+
+    #[test]
+    fn conversion_should_include_base_value() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let request = ConversionRequest::new(Unit::Lb, Unit::Ton, 2.0);
+
+        let response = client
+            .post("/convert?include_base=true")
+            .json(&request)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let response: ConversionResponse = response.into_json().expect("valid json response");
+        assert_that!(response.result).is_equal_to(ConversionResult::Number(0.00090718));
+        assert_that!(response.base_value)
+            .is_some()
+            .is_equal_to(907.18474);
+        assert_that!(response.base_unit)
+            .is_some()
+            .is_equal_to(Unit::Gram);
+    }
+
+    #[test]
+    fn strict_precision_should_refuse_truncated_result() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |from, to| {
+            client
+                .post("/convert?strict_precision=true")
+                .json(&ConversionRequest::new(from, to, 1.0))
+                .dispatch()
+        };
+
+        let truncated = convert(Unit::Kilo, Unit::Lb);
+        let exact = convert(Unit::Kilo, Unit::Gram);
+
+        assert_that!(truncated.status()).is_equal_to(Status::UnprocessableEntity);
+        let body: ErrorBody = truncated.into_json().expect("valid json body");
+        assert_that!(body.error).contains("2.2046226218487757");
+        assert_that!(exact.status()).is_equal_to(Status::Ok);
+    }
+
+    #[test]
+    fn near_integer_result_should_be_snapped() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let snapped = |quantity| {
+            client
+                .post("/convert?snap=true")
+                .json(&ConversionRequest::new(Unit::Gram, Unit::Gram, quantity))
+                .dispatch()
+                .into_json::<ConversionResponse>()
+                .map(|response| response.snapped)
+        };
+
+        assert_that!(snapped(1000.0000001)).contains(Some(1000.0));
+        assert_that!(snapped(2.20000001)).contains(None);
+        assert_that!(snapped(7.00001)).contains(None);
+    }
+
+    #[test]
+    fn identity_conversion_should_have_no_content_when_requested() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |query, from, to, quantity| {
+            client
+                .post(format!("/convert{query}"))
+                .json(&ConversionRequest::new(from, to, quantity))
+                .dispatch()
+                .status()
+        };
+        let no_content = "?no_content_on_identity=true";
+
+        assert_that!(convert(no_content, Unit::Kilo, Unit::Kilo, 2.0))
+            .is_equal_to(Status::NoContent);
+        assert_that!(convert(no_content, Unit::Pascal, Unit::Bar, 0.0))
+            .is_equal_to(Status::NoContent);
+        assert_that!(convert(no_content, Unit::Kilo, Unit::Gram, 2.0)).is_equal_to(Status::Ok);
+        assert_that!(convert("", Unit::Kilo, Unit::Kilo, 2.0)).is_equal_to(Status::Ok);
+    }
+
+    #[test]
+    fn zero_conversion_should_be_well_defined() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |from, to| {
+            client
+                .post("/convert?debug=true&include_base=true&match_input_precision=true")
+                .json(&ConversionRequest::new(from, to, 0.0))
+                .dispatch()
+        };
+
+        let response: ConversionResponse = convert(Unit::Lb, Unit::Ton)
+            .into_json()
+            .expect("valid json response");
+        let reciprocal = convert(Unit::Mpg, Unit::LPer100Km);
+
+        assert_that!(response.result).is_equal_to(ConversionResult::Number(0.0));
+        assert_that!(response.exact).contains(0.0);
+        assert_that!(response.base_value).contains(0.0);
+        assert_that!(response.base_unit).contains(Unit::Gram);
+        assert_that!(reciprocal.status()).is_equal_to(Status::BadRequest);
+    }
+
+    #[test]
+    fn should_convert_msgpack() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let request = ConversionRequest::new(Unit::Kilo, Unit::Lb, 1.0);
+
+        let response = client
+            .post("/convert?formatted=true")
+            .header(ContentType::MsgPack)
+            .body(msgpack::to_vec(&request).expect("serializable request"))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        assert_that!(response.content_type()).contains(ContentType::MsgPack);
+        let body = response.into_bytes().expect("response body");
+        let response: ConversionResponse = msgpack::from_slice(&body).expect("valid msgpack body");
+        assert_that!(response.result).is_equal_to(ConversionResult::Number(2.20462262));
+        assert_that!(response.formatted).contains("2.20462262 lb".to_string());
+    }
+
+    #[test]
+    fn debug_conversion_should_return_exact_result() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let request = ConversionRequest::new(Unit::Kilo, Unit::Lb, 1.0);
+
+        let response = client.post("/convert?debug=true").json(&request).dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let response: ConversionResponse = response.into_json().expect("valid json response");
+        assert_that!(response.result).is_equal_to(ConversionResult::Number(2.20462262));
+        assert_that!(response.exact)
+            .is_some()
+            .is_close_to(2.2046226218, 0.0000000001);
+        assert_that!(response.exact).is_not_equal_to(Some(2.20462262));
+    }
+
+    #[test]
+    fn conversion_between_dimensions_should_fail() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let request = ConversionRequest::new(Unit::Degree, Unit::Kilo, 1.0);
+
+        let response = client.post("/convert").json(&request).dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::BadRequest);
+    }
+
+    #[test]
+    fn disabled_conversion_should_be_forbidden() {
+        let figment = rocket::Config::figment().merge((
+            "disabled_conversions",
+            [UnitPair {
+                from: Unit::Kilo,
+                to: Unit::Lb,
+            }],
+        ));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let request = |from, to| ConversionRequest::new(from, to, 1.0);
+
+        let forbidden = client
+            .post("/convert")
+            .json(&request(Unit::Kilo, Unit::Lb))
+            .dispatch();
+        let allowed = client
+            .post("/convert")
+            .json(&request(Unit::Lb, Unit::Kilo))
+            .dispatch();
+
+        assert_that!(forbidden.status()).is_equal_to(Status::Forbidden);
+        assert_that!(allowed.status()).is_equal_to(Status::Ok);
+    }
+
+    #[test]
+    fn conversion_with_wrong_content_type_should_be_unsupported() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert")
+            .header(ContentType::Plain)
+            .body(r#"{"from": "gram", "to": "kilo", "quantity": 1000}"#)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::UnsupportedMediaType);
+        assert_that!(response.into_json())
+            .is_some()
+            .is_equal_to(ErrorBody {
+                error: "expected application/json or application/msgpack".to_string(),
+                retryable: false,
+            });
+    }
+
+    #[test]
+    fn conversion_should_apply_custom_rounding() {
+        let floor = convert_rounded(Unit::Kilo, Unit::Lb, 1.0, f64::floor);
+        let tenths = convert_rounded(Unit::Kilo, Unit::Lb, 1.0, |exact: f64| {
+            (exact * 10.0).round() / 10.0
+        });
+
+        assert_that!(floor).is_ok().is_equal_to(2.0);
+        assert_that!(tenths).is_ok().is_equal_to(2.2);
+        assert_that!(convert(Unit::Kilo, Unit::Lb, 1.0))
+            .is_ok()
+            .is_equal_to(2.20462262);
+    }
+
+    #[test]
+    fn results_should_be_clamped_to_configured_bounds() {
+        let figment = rocket::Config::figment()
+            .merge(("max_result", 1e9))
+            .merge(("min_result", -10.0));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let convert = |quantity: f64| {
+            client
+                .post("/convert")
+                .json(&ConversionRequest::new(Unit::Ton, Unit::Gram, quantity))
+                .dispatch()
+                .into_json::<ConversionResponse>()
+                .map(|response| (response.result, response.clamped))
+        };
+
+        assert_that!(convert(1e12)).contains((ConversionResult::Number(1e9), Some(true)));
+        assert_that!(convert(-1.0)).contains((ConversionResult::Number(-10.0), Some(true)));
+        assert_that!(convert(1.0)).contains((ConversionResult::Number(1e6), None));
+    }
+
+    #[test]
+    fn should_return_percent_of_input() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |from, to, quantity| {
+            client
+                .post("/convert?as_percent_of_input=true")
+                .json(&ConversionRequest::new(from, to, quantity))
+                .dispatch()
+                .into_json::<ConversionResponse>()
+                .map(|response| response.percent_of_input)
+        };
+
+        assert_that!(convert(Unit::Kilo, Unit::Lb, 1.0)).contains(Some(120.462262));
+        assert_that!(convert(Unit::Lb, Unit::Kilo, 1.0)).contains(Some(-54.640763));
+        assert_that!(convert(Unit::Kilo, Unit::Lb, 0.0)).contains(None);
+    }
+
+    #[test]
+    fn should_flag_derived_rules_when_requested() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |path, from, to| {
+            client
+                .post(path)
+                .json(&ConversionRequest::new(from, to, 1.0))
+                .dispatch()
+                .into_json::<ConversionResponse>()
+                .map(|response| response.derived)
+        };
+
+        let derived = |from, to| convert("/convert?provenance=true", from, to);
+        assert_that!(derived(Unit::Lb, Unit::Kilo)).contains(Some(false));
+        assert_that!(derived(Unit::Gram, Unit::Ton)).contains(Some(true));
+        // The exact reciprocal of a seeded rule
+        assert_that!(derived(Unit::Kilo, Unit::Lb)).contains(Some(false));
+        assert_that!(convert("/convert", Unit::Lb, Unit::Kilo)).contains(None);
+    }
+
+    #[test]
+    fn dimension_mismatch_should_follow_requested_policy() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |policy: &str| {
+            client
+                .post(format!("/convert?on_mismatch={policy}"))
+                .json(&ConversionRequest::new(Unit::Kilo, Unit::Degree, 1.0))
+                .dispatch()
+        };
+
+        assert_that!(convert("error").status()).is_equal_to(Status::BadRequest);
+        assert_that!(convert("null")
+            .into_json::<Value>()
+            .map(|body| body["result"].clone()))
+        .contains(Value::Null);
+        assert_that!(convert("zero").into_json::<ConversionResponse>())
+            .is_some()
+            .is_equal_to(ConversionResponse::plain(ConversionResult::Number(0.0)));
+        assert_that!(client
+            .post("/convert")
+            .json(&ConversionRequest::new(Unit::Kilo, Unit::Degree, 1.0))
+            .dispatch()
+            .status())
+        .is_equal_to(Status::BadRequest);
+    }
+
+    #[test]
+    fn precision_should_be_configurable_per_dimension() {
+        let figment =
+            rocket::Config::figment().merge(("precision", HashMap::from([("temperature", 2)])));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let convert = |from, to| {
+            client
+                .post("/convert")
+                .json(&ConversionRequest::new(from, to, 1.0))
+                .dispatch()
+                .into_json::<ConversionResponse>()
+                .map(|response| response.result)
+        };
+
+        assert_that!(convert(Unit::Fahrenheit, Unit::Kelvin))
+            .contains(ConversionResult::Number(255.93));
+        assert_that!(convert(Unit::Kilo, Unit::Lb)).contains(ConversionResult::Number(2.20462262));
+    }
+
+    #[test]
+    fn subnormal_results_should_follow_configured_policy() {
+        let execute = |subnormal_results| {
+            let converter = Converter {
+                config: Arc::new(Config {
+                    subnormal_results,
+                    ..Config::default()
+                }),
+                ..Converter::default()
+            };
+            ConversionRequest::new(Unit::Gram, Unit::Ton, -1e-303)
+                .execute_with_exact(&converter)
+                .map(|(_, exact)| exact)
+        };
+
+        assert_that!(execute(SubnormalPolicy::Keep))
+            .is_ok()
+            .matches(|exact| exact.is_subnormal());
+        assert_that!(execute(SubnormalPolicy::Flush))
+            .is_ok()
+            .matches(|exact| *exact == 0.0 && exact.is_sign_negative());
+        assert_that!(execute(SubnormalPolicy::Reject).map_err(|err| err.status()))
+            .is_err()
+            .is_equal_to(Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn units_should_display_their_parsable_symbol() {
+        for unit in Unit::ALL {
+            assert_that!(Unit::try_from(unit.to_string().as_str()))
+                .is_ok()
+                .is_equal_to(unit);
+        }
+    }
+
+    #[test]
+    fn malformed_requests_should_never_fail_with_a_server_error() {
+        const PATHS: [&str; 10] = [
+            "/convert",
+            "/convert/batch",
+            "/convert/mixed",
+            "/convert/sweep",
+            "/convert/all",
+            "/convert/array",
+            "/convert/difference",
+            "/convert/stream",
+            "/convert/big",
+            "/convert/rate",
+        ];
+        const UNITS: [&[&str]; 3] = [
+            &[
+                r#""kg""#,
+                r#""lb""#,
+                r#""short ton""#,
+                r#"{"unit":"ton","system":"long"}"#,
+            ],
+            &[r#""°F""#, r#""°C""#, r#""K""#],
+            &[r#""l/100km""#, r#""mpg""#],
+        ];
+        const NUMBERS: [&str; 9] = [
+            "0",
+            "-0.0",
+            "1",
+            "1e308",
+            "-1.7976931348623157e308",
+            "5e-324",
+            "1e-310",
+            "123456789.123456789123",
+            "-459.67",
+        ];
+        const STRINGS: [&str; 7] = [
+            r#""12""#,
+            r#"" -0.5e3 ""#,
+            r#""1e999""#,
+            r#""NaN""#,
+            r#""1 lb 4 oz""#,
+            r#""4 oz 1e308 short ton""#,
+            r#""lb 4""#,
+        ];
+        const MALFORMED: [&str; 7] = [r#""nope""#, r#""rad""#, r#""""#, "null", "[1,2]", "{}", "-"];
+        const OPTIONS: [&str; 17] = [
+            "as_string=true",
+            "grouping=true&thousands_sep=%E2%80%AF",
+            "decimal_sep=",
+            "match_input_precision=true",
+            "strict_precision=true",
+            "snap=true",
+            "formatted=true",
+            "max_input_sigfigs=0",
+            "as_fraction=true",
+            "nearest_fraction=true&denominator=0",
+            "nearest_fraction=true&denominator=4294967295",
+            "as_percent_of_input=true",
+            "provenance=true",
+            "output_base=36",
+            "output_base=1",
+            "notation=engineering&trim=true",
+            "on_mismatch=zero",
+        ];
+
+        // A xorshift generator, seeded so that failures can be reproduced
+        let state = Cell::new(0x2545_f491_4f6c_dd1d_u64);
+        let next = |len: usize| {
+            let mut x = state.get();
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            state.set(x);
+            (x % len as u64) as usize
+        };
+        // Mostly valid values, so that requests get past deserialization to the conversion
+        let pick = |values: &[&'static str]| match next(10) {
+            0 => MALFORMED[next(MALFORMED.len())],
+            _ => values[next(values.len())],
+        };
+
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        for _ in 0..2000 {
+            let (unit, number) = (UNITS[next(UNITS.len())], &NUMBERS[..]);
+            let quantity = match pick(&["number", "string"]) {
+                "number" => pick(number),
+                _ => pick(&STRINGS),
+            };
+            let conversion = format!(
+                concat!(
+                    r#"{{"from":{},"to":{},"through":{},"quantity":{},"quantities":[{},{}],"#,
+                    r#""start":{},"end":{},"step":{},"a":{{"value":{},"unit":{}}},"#,
+                    r#""b":{{"value":{},"unit":{}}},"value":{},"unit":{},"targets":[{},{{"unit":{},"precision":{}}}]}}"#
+                ),
+                pick(unit),
+                pick(unit),
+                pick(unit),
+                quantity,
+                pick(number),
+                pick(number),
+                pick(number),
+                pick(number),
+                pick(number),
+                pick(number),
+                pick(unit),
+                pick(number),
+                pick(unit),
+                pick(number),
+                pick(unit),
+                pick(unit),
+                pick(unit),
+                pick(number),
+            );
+            let body = match next(4) {
+                0 => format!("[{conversion},{conversion}]"),
+                // Cut anywhere, including inside a multibyte character
+                1 => String::from_utf8_lossy(&conversion.as_bytes()[..next(conversion.len())])
+                    .into_owned(),
+                _ => conversion,
+            };
+            let path = format!(
+                "{}?{}&{}",
+                PATHS[next(PATHS.len())],
+                OPTIONS[next(OPTIONS.len())],
+                OPTIONS[next(OPTIONS.len())]
+            );
+
+            let response = client
+                .post(path.as_str())
+                .header(ContentType::JSON)
+                .body(&body)
+                .dispatch();
+
+            let description = format!("{path} {body}");
+            asserting!(&description)
+                .that(&response.status().code)
+                .is_less_than(500);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::format::OutputOptions;
+use crate::{system, ConversionRequest, ConversionResponse, Unit};
+use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
+use rocket::State;
+
+/// Convert a quantity made of several units, such as `1 lb 4 oz`, to a single unit.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct MixedConversionRequest {
+    quantity: String,
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    to: Unit,
+}
+
+/// Sum the components of the mixed quantity in the base unit of their dimension,
+/// then convert the sum to the target unit. The query string controls how the result is
+/// displayed (see [`OutputOptions`]).
+#[post("/convert/mixed?<options..>", data = "<conversion>")]
+pub fn convert_mixed(
+    conversion: Json<MixedConversionRequest>,
+    options: OutputOptions,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<ConversionResponse>, ConvertError<'static>> {
+    let components = parse(&conversion.quantity)?;
+    let base_unit = match components.first() {
+        Some((_, unit)) => unit.dimension().base_unit(),
+        None => {
+            return Err(ConvertError::InvalidMixedQuantity {
+                quantity: conversion.quantity.clone(),
+                reason: "the quantity is empty".to_string(),
+            })
+        }
+    };
+
+    let total = components
+        .into_iter()
+        .map(|(quantity, unit)| {
+            ConversionRequest::new(unit, base_unit, quantity)
+                .execute_with_exact(&converter)
+                .map(|(_, exact)| exact)
+        })
+        .sum::<Result<f64, _>>()?;
+
+    let request = ConversionRequest::new(base_unit, conversion.to, total);
+    let response = ConversionResponse::with_options(&request, None, &options, &converter)?;
+    quotas.consume(&api_key, 1)?;
+    Ok(Json(response))
+}
+
+/// Split a mixed quantity into its components, each one a number followed by a unit symbol.
+/// A unit symbol can span several words, such as `short ton`.
+fn parse(quantity: &str) -> Result<Vec<(f64, Unit)>, ConvertError<'static>> {
+    let invalid = |reason: String| ConvertError::InvalidMixedQuantity {
+        quantity: quantity.to_string(),
+        reason,
+    };
+
+    let mut components = vec![];
+    let mut words = quantity.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        let number: f64 = word
+            .parse()
+            .map_err(|_| invalid(format!("expected a number, got '{word}'")))?;
+
+        let mut symbol = vec![];
+        while let Some(word) = words.next_if(|word| word.parse::<f64>().is_err()) {
+            symbol.push(word);
+        }
+
+        if symbol.is_empty() {
+            return Err(invalid(format!("missing the unit of {number}")));
+        }
+
+        let unit =
+            Unit::try_from(symbol.join(" ").as_str()).map_err(|err| invalid(err.to_string()))?;
+        components.push((number, unit));
+    }
+
+    if components.is_empty() {
+        return Err(invalid("the quantity is empty".to_string()));
+    }
+
+    Ok(components)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+    use crate::error::ErrorBody;
+    use crate::{rocket, Unit};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_parse_components() {
+        assert_that!(parse("1 short ton 4.5 lb"))
+            .is_ok()
+            .is_equal_to(vec![(1.0, Unit::ShortTon), (4.5, Unit::Lb)]);
+        assert_that!(parse("1 lb 4")).is_err();
+        assert_that!(parse("lb")).is_err();
+        assert_that!(parse("")).is_err();
+    }
+
+    #[test]
+    fn should_convert_mixed_quantity() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/mixed")
+            .json(&json!({"quantity": "1 lb 4 oz", "to": "gram"}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_f64())
+            .is_some()
+            .is_close_to(566.99046, 0.00001);
+    }
+
+    #[test]
+    fn should_convert_to_a_symbol_or_a_unit_qualified_by_its_system() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let convert = |to| {
+            client
+                .post("/convert/mixed")
+                .json(&json!({"quantity": "1 short ton 1000 lb", "to": to}))
+                .dispatch()
+                .into_json::<Value>()
+                .and_then(|body| body["result"].as_f64())
+        };
+
+        assert_that!(convert(json!("kg"))).contains(1360.77711);
+        assert_that!(convert(json!({"unit": "ton", "system": "short"}))).contains(1.5);
+    }
+
+    #[test]
+    fn should_refuse_components_of_several_dimensions() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/mixed")
+            .json(&json!({"quantity": "1 lb 4 rad", "to": "gram"}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::BadRequest);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error).contains("do not measure the same dimension");
+    }
+}
@@ -0,0 +1,25 @@
+use rocket::http::{ContentType, Status};
+use rocket::response::{self, Responder};
+use rocket::serde::{msgpack, Serialize};
+use rocket::{Request, Response};
+use std::io::Cursor;
+
+/// A MessagePack response writing structs as maps keyed by field name.
+///
+/// Rocket's `MsgPack` responder writes structs as arrays of their fields, which cannot be read
+/// back once optional fields are skipped.
+pub struct NamedMsgPack<T>(pub T);
+
+impl<'r, 'o: 'r, T: Serialize> Responder<'r, 'o> for NamedMsgPack<T> {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
+        let body = msgpack::to_vec(&self.0).map_err(|err| {
+            error!("MessagePack failed to serialize: {:?}", err);
+            Status::InternalServerError
+        })?;
+
+        Response::build()
+            .header(ContentType::MsgPack)
+            .sized_body(body.len(), Cursor::new(body))
+            .ok()
+    }
+}
@@ -0,0 +1,274 @@
+use crate::alias;
+use crate::converter::SharedConverter;
+use crate::system;
+use rocket::data::{FromData, Limits, Outcome};
+use rocket::http::Status;
+use rocket::serde::de::Error;
+use rocket::serde::json::serde_json::value::RawValue;
+use rocket::serde::json::{self, serde_json};
+use rocket::serde::{Deserialize, Deserializer};
+use rocket::{Data, Request};
+use std::fmt::Display;
+use std::io;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", untagged)]
+enum NumberOrString<T> {
+    Number(T),
+    String(String),
+}
+
+/// Deserialize a numeric field from either a JSON number or a numeric string,
+/// for clients sending `"2.5"` rather than `2.5`.
+pub fn number_or_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    match NumberOrString::<T>::deserialize(deserializer)? {
+        NumberOrString::Number(number) => Ok(number),
+        NumberOrString::String(number) => number.trim().parse().map_err(D::Error::custom),
+    }
+}
+
+/// A JSON body along with the raw text of its `quantity` field, as sent by the client
+/// and before it is parsed to a floating point number, and the unit names it uses.
+pub struct WithRawQuantity<T> {
+    pub value: T,
+    /// The quantity digits, without the quotes of a numeric string, if there is a quantity.
+    pub raw_quantity: Option<String>,
+    /// The `from`, `to` and `through` units written as a name or a symbol, rather than
+    /// qualified by their system.
+    pub unit_names: Vec<String>,
+}
+
+/// The request body, cached for the lifetime of the request so the parsed value can borrow it.
+struct RawBody(String);
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct RawFields<'a> {
+    #[serde(borrow)]
+    quantity: Option<&'a RawValue>,
+    #[serde(borrow)]
+    from: Option<&'a RawValue>,
+    #[serde(borrow)]
+    to: Option<&'a RawValue>,
+    #[serde(borrow)]
+    through: Option<&'a RawValue>,
+}
+
+/// Parse an integer written in `base`, between 2 and 36, optionally signed and prefixed
+/// with the `0x`, `0o` or `0b` prefix of its base.
+pub fn parse_integer(digits: &str, base: u32) -> Option<i64> {
+    if !(2..=36).contains(&base) {
+        return None;
+    }
+
+    let digits = digits.trim();
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, digits.strip_prefix('+').unwrap_or(digits)),
+    };
+    let prefix = match base {
+        16 => "0x",
+        8 => "0o",
+        2 => "0b",
+        _ => "",
+    };
+    let digits = digits
+        .strip_prefix(prefix)
+        .or_else(|| digits.strip_prefix(&prefix.to_uppercase()))
+        .unwrap_or(digits);
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+
+    let value = i64::from_str_radix(digits, base).ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Rewrite the `quantity` of a JSON body, an integer written in `base`, as a JSON number.
+fn rewrite_quantity(body: &str, base: u32) -> Result<String, String> {
+    let mut value: serde_json::Value = serde_json::from_str(body).map_err(|err| err.to_string())?;
+    if let Some(quantity) = value.get_mut("quantity") {
+        let digits = match &*quantity {
+            serde_json::Value::String(digits) => digits.clone(),
+            other => other.to_string(),
+        };
+        let integer = parse_integer(&digits, base)
+            .ok_or_else(|| format!("'{digits}' is not an integer in base {base}"))?;
+        *quantity = integer.into();
+    }
+
+    Ok(value.to_string())
+}
+
+/// Add the base unit of the `to` dimension as the `from` unit of a JSON body, `None` when
+/// the body already has a `from` unit or its `to` unit is invalid.
+fn with_default_from(body: &str) -> Option<String> {
+    let mut value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let fields = value.as_object_mut()?;
+    if fields.contains_key("from") {
+        return None;
+    }
+
+    let to = system::unit_or_qualified(fields.get("to")?).ok()?;
+    let from = serde_json::to_value(to.dimension().base_unit()).ok()?;
+    fields.insert("from".to_string(), from);
+    Some(value.to_string())
+}
+
+impl<T> WithRawQuantity<T> {
+    fn from_str<'r>(body: &'r str) -> Result<Self, json::Error<'r>>
+    where
+        T: Deserialize<'r>,
+    {
+        let parse = |err| json::Error::Parse(body, err);
+        let value = serde_json::from_str(body).map_err(parse)?;
+        let fields = serde_json::from_str::<RawFields>(body).map_err(parse)?;
+        let raw_quantity = fields.quantity.map(|raw| {
+            serde_json::from_str::<String>(raw.get()).unwrap_or_else(|_| raw.get().to_string())
+        });
+        let unit_names = [fields.from, fields.to, fields.through]
+            .into_iter()
+            .flatten()
+            .filter_map(|raw| serde_json::from_str::<String>(raw.get()).ok())
+            .collect();
+
+        Ok(WithRawQuantity {
+            value,
+            raw_quantity,
+            unit_names,
+        })
+    }
+}
+
+/// Read the body the way [`json::Json`] does, answering with the same error statuses.
+/// When the query string has an `input_base` other than 10, the quantity is read as an integer
+/// written in that base. Units designated by a configured alias are replaced by the unit they
+/// stand for. When `default_from_base_unit` is configured, a missing `from` unit
+/// is the base unit of the `to` dimension.
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for WithRawQuantity<T> {
+    type Error = json::Error<'r>;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let limit = request.limits().get("json").unwrap_or(Limits::JSON);
+        let body = match data.open(limit).into_string().await {
+            Ok(body) if body.is_complete() => body.into_inner(),
+            Ok(_) => {
+                let err = io::Error::new(io::ErrorKind::UnexpectedEof, "data limit exceeded");
+                return Outcome::Error((Status::PayloadTooLarge, json::Error::Io(err)));
+            }
+            Err(err) => return Outcome::Error((Status::BadRequest, json::Error::Io(err))),
+        };
+
+        let body = match request.query_value::<u32>("input_base") {
+            None | Some(Ok(10)) => body,
+            Some(base) => match base
+                .map_err(|err| err.to_string())
+                .and_then(|base| rewrite_quantity(&body, base))
+            {
+                Ok(body) => body,
+                Err(reason) => {
+                    let err = io::Error::new(io::ErrorKind::InvalidData, reason);
+                    return Outcome::Error((Status::UnprocessableEntity, json::Error::Io(err)));
+                }
+            },
+        };
+
+        let converter = request
+            .rocket()
+            .state::<SharedConverter>()
+            .map(SharedConverter::current);
+        let body = match &converter {
+            Some(converter) if !converter.config.aliases.is_empty() => {
+                alias::resolve_body(&body, &converter.config.aliases).unwrap_or(body)
+            }
+            _ => body,
+        };
+        let default_from = converter
+            .as_ref()
+            .is_some_and(|converter| converter.config.default_from_base_unit);
+        let body = if default_from {
+            with_default_from(&body).unwrap_or(body)
+        } else {
+            body
+        };
+
+        match Self::from_str(&request.local_cache(|| RawBody(body)).0) {
+            Ok(value) => Outcome::Success(value),
+            Err(json::Error::Parse(body, err))
+                if err.classify() == serde_json::error::Category::Data =>
+            {
+                Outcome::Error((Status::UnprocessableEntity, json::Error::Parse(body, err)))
+            }
+            Err(err) => Outcome::Error((Status::BadRequest, err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::numeric::parse_integer;
+    use crate::{rocket, ConversionRequest};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::{Client, LocalResponse};
+    use rocket::serde::json::{serde_json, Value};
+    use speculoos::prelude::*;
+
+    fn quantity(json: &str) -> Result<f64, serde_json::Error> {
+        serde_json::from_str::<ConversionRequest>(json).map(|request| request.quantity)
+    }
+
+    #[test]
+    fn should_accept_numeric_string_quantity() {
+        let number = quantity(r#"{"from": "kilo", "to": "gram", "quantity": 2}"#);
+        let string = quantity(r#"{"from": "kilo", "to": "gram", "quantity": " 2 "}"#);
+
+        assert_that!(number).is_ok().is_equal_to(2.0);
+        assert_that!(string).is_ok().is_equal_to(2.0);
+    }
+
+    #[test]
+    fn should_parse_integers_in_any_base() {
+        assert_that!(parse_integer("ff", 16)).contains(255);
+        assert_that!(parse_integer(" -0xFF ", 16)).contains(-255);
+        assert_that!(parse_integer("0b101", 2)).contains(5);
+        assert_that!(parse_integer("z", 36)).contains(35);
+        assert_that!(parse_integer("12", 2)).is_none();
+        assert_that!(parse_integer("--1", 16)).is_none();
+        assert_that!(parse_integer("1", 37)).is_none();
+    }
+
+    #[test]
+    fn missing_from_should_default_to_base_unit_when_configured() {
+        fn convert(client: &Client) -> LocalResponse<'_> {
+            client
+                .post("/convert")
+                .header(ContentType::JSON)
+                .body(r#"{"to": "kilo", "quantity": 1500}"#)
+                .dispatch()
+        }
+
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        assert_that!(convert(&client).status()).is_equal_to(Status::UnprocessableEntity);
+
+        let figment = rocket::Config::figment().merge(("default_from_base_unit", true));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let response = convert(&client);
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_f64()).contains(1.5);
+    }
+
+    #[test]
+    fn should_reject_non_numeric_string_quantity() {
+        let result = quantity(r#"{"from": "kilo", "to": "gram", "quantity": "two"}"#);
+
+        assert_that!(result).is_err();
+    }
+}
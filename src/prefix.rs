@@ -0,0 +1,87 @@
+/// An SI prefix applied to a metric base unit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Prefix {
+    Micro,
+    Milli,
+    None,
+    Kilo,
+    Mega,
+}
+
+impl Prefix {
+    /// The power of ten this prefix stands for.
+    pub fn exponent(self) -> i32 {
+        match self {
+            Prefix::Micro => -6,
+            Prefix::Milli => -3,
+            Prefix::None => 0,
+            Prefix::Kilo => 3,
+            Prefix::Mega => 6,
+        }
+    }
+
+    /// Parse a prefix symbol, the empty string standing for the unprefixed base unit.
+    /// Micro can be written with the micro sign, the greek letter mu or the ASCII letter u.
+    pub fn from_symbol(symbol: &str) -> Option<Prefix> {
+        match symbol {
+            "\u{b5}" | "\u{3bc}" | "u" => Some(Prefix::Micro),
+            "m" => Some(Prefix::Milli),
+            "" => Some(Prefix::None),
+            "k" => Some(Prefix::Kilo),
+            "M" => Some(Prefix::Mega),
+            _ => None,
+        }
+    }
+
+    /// The factor converting a quantity expressed with this prefix to the `other` prefix.
+    pub fn factor_to(self, other: Prefix) -> f64 {
+        let exponent = self.exponent() - other.exponent();
+        // Dividing by a positive power of ten gives the closest f64 to the decimal factor,
+        // which is not the case of `10.0.powi` with a negative exponent.
+        if exponent >= 0 {
+            10_f64.powi(exponent)
+        } else {
+            1.0 / 10_f64.powi(-exponent)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prefix::Prefix;
+    use crate::Unit;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_parse_prefixed_grams() {
+        assert_that!(Unit::try_from("µg").ok()).contains(Unit::Microgram);
+        assert_that!(Unit::try_from("mg").ok()).contains(Unit::Milligram);
+        assert_that!(Unit::try_from("g").ok()).contains(Unit::Gram);
+        assert_that!(Unit::try_from("kg").ok()).contains(Unit::Kilo);
+        assert_that!(Unit::try_from("Mg").ok()).contains(Unit::Ton);
+    }
+
+    #[test]
+    fn should_parse_micro_symbol_variants() {
+        assert_that!(Unit::try_from("\u{b5}g").ok()).contains(Unit::Microgram);
+        assert_that!(Unit::try_from("\u{3bc}g").ok()).contains(Unit::Microgram);
+        assert_that!(Unit::try_from("ug").ok()).contains(Unit::Microgram);
+    }
+
+    #[test]
+    fn should_parse_degree_sign() {
+        assert_that!(Unit::try_from("°").ok()).contains(Unit::Degree);
+        assert_that!(Unit::try_from("deg").ok()).contains(Unit::Degree);
+    }
+
+    #[test]
+    fn should_reject_unknown_prefix() {
+        assert_that!(Unit::try_from("xg")).is_err();
+    }
+
+    #[test]
+    fn factor_between_prefixes() {
+        assert_that!(Prefix::Milli.factor_to(Prefix::None)).is_equal_to(0.001);
+        assert_that!(Prefix::Mega.factor_to(Prefix::Micro)).is_equal_to(1_000_000_000_000.0);
+    }
+}
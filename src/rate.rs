@@ -0,0 +1,163 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::conversion::{ConversionRule, RuleKind};
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::format::{self, Decimals, RoundingStrategy};
+use crate::{numeric, units, Unit};
+use rocket::serde::de::Error;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Deserializer, Serialize};
+use rocket::State;
+
+/// A quantity of the `numerator` unit per `denominator` unit, such as `kg/h`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Rate {
+    pub numerator: Unit,
+    pub denominator: Unit,
+}
+
+impl Rate {
+    /// Parse a rate written `<unit>/<unit>` or `<unit> per <unit>`, each unit given by its name
+    /// or symbol, such as `kg/h` or `kilo per hour`. The units are split on the last `/`, so that
+    /// `L/100km/s` is a rate of `L/100km`.
+    fn parse(rate: &str) -> Result<Rate, String> {
+        let (numerator, denominator) =
+            rate.split_once(" per ")
+                .or_else(|| rate.rsplit_once('/'))
+                .ok_or_else(|| format!("Invalid rate '{rate}', expected '<unit>/<unit>'"))?;
+
+        let unit = |name: &str| units::parse(name.trim()).map_err(|err| err.to_string());
+        Ok(Rate {
+            numerator: unit(numerator)?,
+            denominator: unit(denominator)?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Rate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Rate::parse(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/// Convert a quantity from a rate to another, such as `kg/h` to `g/min`.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct RateConversionRequest {
+    pub from: Rate,
+    pub to: Rate,
+    #[serde(deserialize_with = "numeric::number_or_string")]
+    pub quantity: f64,
+}
+
+/// The converted rate, rounded to the precision configured for the dimension of its numerator.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct RateConversion {
+    #[serde(serialize_with = "format::serialize_number")]
+    pub result: f64,
+}
+
+/// The factor of a rule scaling quantities without an offset, as rates can only be converted
+/// between proportional units: 1 °C per hour is not 33.8 °F per hour.
+fn proportional_factor(
+    rule: ConversionRule,
+    from: Unit,
+    to: Unit,
+) -> Result<f64, ConvertError<'static>> {
+    if rule.kind() == RuleKind::Reciprocal || rule.offset() != 0.0 {
+        return Err(ConvertError::NotProportional { from, to });
+    }
+
+    Ok(rule.factor())
+}
+
+/// Convert the numerator and the denominator of a rate separately, then combine them:
+/// `60 kg/h` is `60 * 1000 / 60 = 1000 g/min`.
+#[post("/convert/rate", data = "<conversion>")]
+pub fn convert_rate(
+    conversion: Json<RateConversionRequest>,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<RateConversion>, ConvertError<'static>> {
+    let RateConversionRequest { from, to, quantity } = conversion.into_inner();
+    let factor =
+        |from: Unit, to: Unit| proportional_factor(from.rule_to(to, &converter)?, from, to);
+
+    let exact = quantity * factor(from.numerator, to.numerator)?
+        / factor(from.denominator, to.denominator)?;
+    if !exact.is_finite() {
+        return Err(ConvertError::NonFiniteResult {
+            from: from.numerator,
+            to: to.numerator,
+            quantity,
+        });
+    }
+
+    quotas.consume(&api_key, 1)?;
+    let decimals = converter.config.decimals(to.numerator.dimension());
+    Ok(Json(RateConversion {
+        result: Decimals(decimals).round(exact),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rate;
+    use crate::error::ErrorBody;
+    use crate::rate::RateConversion;
+    use crate::{rocket, Unit};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_parse_rates() {
+        let rate = |numerator, denominator| Rate {
+            numerator,
+            denominator,
+        };
+
+        assert_that!(Rate::parse("kg/h"))
+            .is_ok()
+            .is_equal_to(rate(Unit::Kilo, Unit::Hour));
+        assert_that!(Rate::parse("gram per minute"))
+            .is_ok()
+            .is_equal_to(rate(Unit::Gram, Unit::Minute));
+        assert_that!(Rate::parse("L/100km/s"))
+            .is_ok()
+            .is_equal_to(rate(Unit::LPer100Km, Unit::Second));
+        assert_that!(Rate::parse("kg")).is_err();
+        assert_that!(Rate::parse("kg/nope")).is_err();
+    }
+
+    #[test]
+    fn should_convert_numerator_and_denominator() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/rate")
+            .json(&json!({"from": "kg/hour", "to": "g/minute", "quantity": 60}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let body: RateConversion = response.into_json().expect("valid json body");
+        assert_that!(body.result).is_equal_to(1000.0);
+    }
+
+    #[test]
+    fn should_refuse_rates_of_non_proportional_units() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/rate")
+            .json(&json!({"from": "°C/h", "to": "°F/h", "quantity": 1}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error).contains("not proportional");
+    }
+}
@@ -0,0 +1,495 @@
+#[cfg(feature = "admin")]
+use crate::admin::AdminToken;
+use crate::conversion::{ConversionRule, ConversionTable, RuleKind, CONVERSION_TABLE};
+#[cfg(feature = "admin")]
+use crate::converter::SharedConverter;
+use crate::converter::{Converter, Overrides};
+#[cfg(feature = "admin")]
+use crate::error::ConvertError;
+#[cfg(feature = "admin")]
+use crate::idempotency::IdempotencyStore;
+#[cfg(feature = "admin")]
+use crate::ConversionResponse;
+use crate::Unit;
+#[cfg(feature = "admin")]
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+#[cfg(feature = "admin")]
+use rocket::State;
+
+/// Relative difference under which a proposed factor or offset matches the existing one.
+const MATCH_TOLERANCE: f64 = 1e-9;
+
+/// A problem making a proposed rule unfit for the conversion table.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde", tag = "issue", rename_all = "snake_case")]
+pub enum RuleIssue {
+    /// The units do not measure the same dimension.
+    DimensionMismatch,
+    /// A zero factor would convert every quantity to the offset.
+    ZeroFactor,
+    /// A negative factor would reverse the sign of every quantity.
+    NegativeFactor,
+    /// An infinite or NaN factor or offset has no meaningful conversion nor inverse.
+    NonFiniteFactor,
+    /// Reciprocal rules cannot have an offset.
+    ReciprocalOffset,
+    /// The table already converts between these units differently.
+    Conflict {
+        factor: f64,
+        offset: f64,
+        kind: RuleKind,
+    },
+}
+
+/// The outcome of a rule validation, `valid` when there is no issue.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct RuleValidation {
+    pub valid: bool,
+    pub issues: Vec<RuleIssue>,
+}
+
+/// A rule of the active table converting differently than the reference table.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct RuleDiff {
+    pub from: Unit,
+    pub to: Unit,
+    pub factor: f64,
+    pub reference_factor: f64,
+    pub offset: f64,
+    pub reference_offset: f64,
+    /// `(factor - reference_factor) / reference_factor`
+    pub relative_difference: f64,
+}
+
+fn matches(a: f64, b: f64) -> bool {
+    (a - b).abs() <= MATCH_TOLERANCE * a.abs().max(b.abs())
+}
+
+/// Every rule of `active` whose factor or offset differs from the rule of `reference`
+/// between the same units, sorted by source then target unit.
+pub fn diff(active: &ConversionTable, reference: &ConversionTable) -> Vec<RuleDiff> {
+    let mut diffs: Vec<RuleDiff> = active
+        .rules()
+        .filter_map(|rule| {
+            let other = reference.find(rule.from, rule.to)?;
+            let differs =
+                !matches(rule.factor(), other.factor()) || !matches(rule.offset(), other.offset());
+
+            differs.then(|| RuleDiff {
+                from: rule.from,
+                to: rule.to,
+                factor: rule.factor(),
+                reference_factor: other.factor(),
+                offset: rule.offset(),
+                reference_offset: other.offset(),
+                relative_difference: (rule.factor() - other.factor()) / other.factor(),
+            })
+        })
+        .collect();
+
+    diffs.sort_by_key(|diff| (diff.from, diff.to));
+    diffs
+}
+
+/// Every issue of a proposed rule, checked against the rules of `table`.
+pub fn validate(rule: &ConversionRule, table: &ConversionTable) -> Vec<RuleIssue> {
+    let mut issues = vec![];
+
+    if rule.from.dimension() != rule.to.dimension() {
+        issues.push(RuleIssue::DimensionMismatch);
+    }
+
+    if rule.factor() == 0.0 {
+        issues.push(RuleIssue::ZeroFactor);
+    } else if rule.factor() < 0.0 {
+        issues.push(RuleIssue::NegativeFactor);
+    }
+
+    if !rule.factor().is_finite() || !rule.offset().is_finite() {
+        issues.push(RuleIssue::NonFiniteFactor);
+    }
+
+    if rule.kind() == RuleKind::Reciprocal && rule.offset() != 0.0 {
+        issues.push(RuleIssue::ReciprocalOffset);
+    }
+
+    if let Some(existing) = table.find(rule.from, rule.to) {
+        if existing.kind() != rule.kind()
+            || !matches(existing.factor(), rule.factor())
+            || !matches(existing.offset(), rule.offset())
+        {
+            issues.push(RuleIssue::Conflict {
+                factor: existing.factor(),
+                offset: existing.offset(),
+                kind: existing.kind(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Check a proposed rule against the conversion table, without changing it.
+#[post("/rules/validate", data = "<rule>")]
+pub fn validate_rule(rule: Json<ConversionRule>, converter: Converter) -> Json<RuleValidation> {
+    let issues = validate(&rule, &converter.table);
+    Json(RuleValidation {
+        valid: issues.is_empty(),
+        issues,
+    })
+}
+
+/// Add a rule to the conversion table, taking precedence over the built-in and overridden
+/// rules between the same units. Rules with any issue other than a conflict are refused
+/// with a `422 Unprocessable Entity` status and the issues found. The cached responses are
+/// cleared, they may have been computed with the previous rules.
+/// Only available with the `admin` feature.
+#[cfg(feature = "admin")]
+#[post("/rules", data = "<rule>")]
+pub fn add_rule(
+    _token: AdminToken,
+    rule: Json<ConversionRule>,
+    converter: &State<SharedConverter>,
+    store: &State<IdempotencyStore<ConversionResponse>>,
+) -> Result<(Status, Json<RuleValidation>), ConvertError<'static>> {
+    let issues = converter.add_rule(rule.into_inner())?;
+    if !issues.is_empty() {
+        let validation = RuleValidation {
+            valid: false,
+            issues,
+        };
+        return Ok((Status::UnprocessableEntity, Json(validation)));
+    }
+
+    store.clear();
+    let validation = RuleValidation {
+        valid: true,
+        issues,
+    };
+    Ok((Status::Created, Json(validation)))
+}
+
+/// Compare the active conversion table, with its overridden and added rules, to the table
+/// built from the built-in conversions only.
+#[get("/rules/diff")]
+pub fn rules_diff(converter: Converter) -> Json<Vec<RuleDiff>> {
+    Json(diff(&converter.table, &CONVERSION_TABLE))
+}
+
+/// Export every rule of the active conversion table, with its overridden and added rules,
+/// as an overrides file restoring the same table when configured as `overrides_file`.
+#[get("/rules/export")]
+pub fn export(converter: Converter) -> Json<Overrides> {
+    let mut rules: Vec<ConversionRule> = converter.table.rules().copied().collect();
+    rules.sort_by_key(|rule| (rule.from, rule.to));
+
+    Json(Overrides {
+        version: converter.overrides_version.clone(),
+        rules,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "admin")]
+    use crate::admin::ADMIN_TOKEN_HEADER;
+    use crate::conversion::{ConversionRule, ConversionTable, RuleKind, CONVERSION_TABLE};
+    #[cfg(feature = "admin")]
+    use crate::error::ErrorBody;
+    #[cfg(feature = "admin")]
+    use crate::idempotency::{IdempotencyStore, IDEMPOTENCY_KEY_HEADER};
+    use crate::rocket;
+    use crate::rules::{self, diff, RuleDiff, RuleIssue, RuleValidation};
+    use crate::Unit;
+    #[cfg(feature = "admin")]
+    use crate::{ConversionRequest, ConversionResponse, ConversionResult};
+    use rocket::figment::providers::{Format, Toml};
+    use rocket::figment::Figment;
+    #[cfg(feature = "admin")]
+    use rocket::http::Header;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    #[cfg(feature = "admin")]
+    use rocket::local::blocking::LocalResponse;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+
+    fn validate(rule: rocket::serde::json::Value) -> RuleValidation {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let response = client.post("/rules/validate").json(&rule).dispatch();
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        response.into_json().expect("valid json body")
+    }
+
+    #[cfg(feature = "admin")]
+    fn admin_client(figment: Figment) -> Client {
+        let figment = figment.merge(("admin_token", "secret"));
+        Client::untracked(rocket().configure(figment)).expect("valid rocket instance")
+    }
+
+    #[cfg(feature = "admin")]
+    fn add_rule(client: &Client, rule: rocket::serde::json::Value) -> LocalResponse<'_> {
+        client
+            .post("/rules")
+            .header(Header::new(ADMIN_TOKEN_HEADER, "secret"))
+            .json(&rule)
+            .dispatch()
+    }
+
+    #[test]
+    fn should_reject_zero_factor() {
+        let validation = validate(json!({"from": "lb", "to": "kilo", "factor": 0.0}));
+
+        assert_that!(validation.valid).is_false();
+        assert_that!(validation.issues).contains(RuleIssue::ZeroFactor);
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_refuse_negative_factor() {
+        let client = admin_client(rocket::Config::figment());
+
+        let response = add_rule(&client, json!({"from": "kilo", "to": "lb", "factor": -2.2}));
+
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+        let validation: RuleValidation = response.into_json().expect("valid json body");
+        assert_that!(validation.issues).is_equal_to(vec![RuleIssue::NegativeFactor]);
+    }
+
+    #[test]
+    fn should_reject_non_finite_offset() {
+        // JSON cannot carry an infinite number, TOML can
+        let rule: ConversionRule = Figment::from(Toml::string(
+            "from = 'celsius'\nto = 'fahrenheit'\nfactor = 1.8\noffset = inf",
+        ))
+        .extract()
+        .expect("valid rule");
+
+        let issues = rules::validate(&rule, &CONVERSION_TABLE);
+
+        assert_that!(issues).contains(RuleIssue::NonFiniteFactor);
+        assert_that!(issues).does_not_contain(RuleIssue::ZeroFactor);
+    }
+
+    #[test]
+    fn should_reject_rule_between_dimensions() {
+        let validation = validate(json!({"from": "lb", "to": "degree", "factor": 2.0}));
+
+        assert_that!(validation.issues).contains(RuleIssue::DimensionMismatch);
+    }
+
+    #[test]
+    fn should_report_conflicting_factor() {
+        let validation = validate(json!({"from": "kilo", "to": "gram", "factor": 999.0}));
+
+        assert_that!(validation.issues).is_equal_to(vec![RuleIssue::Conflict {
+            factor: 1000.0,
+            offset: 0.0,
+            kind: RuleKind::Affine,
+        }]);
+    }
+
+    #[test]
+    fn should_accept_rule_matching_the_table() {
+        let validation = validate(json!({"from": "kilo", "to": "gram", "factor": 1000.0}));
+
+        assert_that!(validation).is_equal_to(RuleValidation {
+            valid: true,
+            issues: vec![],
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_apply_added_rule() {
+        let client = admin_client(rocket::Config::figment());
+
+        let response = add_rule(&client, json!({"from": "kilo", "to": "lb", "factor": 2.0}));
+        assert_that!(response.status()).is_equal_to(Status::Created);
+
+        let response = client
+            .post("/convert")
+            .json(&json!({"from": "gram", "to": "lb", "quantity": 500}))
+            .dispatch();
+        let body: rocket::serde::json::Value = response.into_json().expect("valid json body");
+        assert_that!(body["result"].as_f64()).contains(1.0);
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_refuse_invalid_rule() {
+        let client = admin_client(rocket::Config::figment());
+
+        let response = add_rule(&client, json!({"from": "kilo", "to": "lb", "factor": 0.0}));
+
+        assert_that!(response.status()).is_equal_to(Status::UnprocessableEntity);
+        let validation: RuleValidation = response.into_json().expect("valid json body");
+        assert_that!(validation.issues).is_equal_to(vec![RuleIssue::ZeroFactor]);
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_refuse_to_add_rule_without_admin_token() {
+        let client = admin_client(rocket::Config::figment());
+
+        let response = client
+            .post("/rules")
+            .json(&json!({"from": "kilo", "to": "lb", "factor": 2.0}))
+            .dispatch();
+        assert_that!(response.status()).is_equal_to(Status::Unauthorized);
+
+        let response = client
+            .post("/convert")
+            .json(&ConversionRequest::new(Unit::Kilo, Unit::Lb, 1.0))
+            .dispatch()
+            .into_json::<ConversionResponse>();
+        assert_that!(response.map(|response| response.result))
+            .contains(ConversionResult::Number(2.20462262));
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_clear_cached_responses_when_adding_rule() {
+        let client = admin_client(rocket::Config::figment());
+        let convert = || {
+            client
+                .post("/convert")
+                .header(Header::new(IDEMPOTENCY_KEY_HEADER, "kilo-to-lb"))
+                .json(&ConversionRequest::new(Unit::Kilo, Unit::Lb, 1.0))
+                .dispatch()
+                .into_json::<ConversionResponse>()
+                .map(|response| response.result)
+        };
+
+        assert_that!(convert()).contains(ConversionResult::Number(2.20462262));
+        let response = add_rule(&client, json!({"from": "kilo", "to": "lb", "factor": 2.0}));
+        assert_that!(response.status()).is_equal_to(Status::Created);
+
+        let store = client
+            .rocket()
+            .state::<IdempotencyStore<ConversionResponse>>()
+            .expect("managed idempotency store");
+        assert_that!(store.len()).is_equal_to(0);
+        assert_that!(convert()).contains(ConversionResult::Number(2.0));
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_refuse_rules_beyond_the_limit() {
+        let client = admin_client(rocket::Config::figment().merge(("max_custom_rules", 2)));
+        let add = |rule| add_rule(&client, rule).status();
+
+        assert_that!(add(json!({"from": "kilo", "to": "lb", "factor": 2.0})))
+            .is_equal_to(Status::Created);
+        assert_that!(add(json!({"from": "bar", "to": "psi", "factor": 14.0})))
+            .is_equal_to(Status::Created);
+        // Replacing a custom rule does not count towards the limit
+        assert_that!(add(json!({"from": "lb", "to": "kilo", "factor": 0.5})))
+            .is_equal_to(Status::Created);
+
+        let response = add_rule(
+            &client,
+            json!({"from": "joule", "to": "calorie", "factor": 0.25}),
+        );
+        assert_that!(response.status()).is_equal_to(Status::Conflict);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error).contains("2 custom rules have already been added");
+    }
+
+    #[test]
+    fn should_diff_tables_differing_on_one_factor() {
+        let reference = ConversionTable::build(&[]);
+        let active = ConversionTable::build(&[
+            ConversionRule::try_from(&["bar", "Pa", "100001"]).expect("valid rule")
+        ]);
+
+        let diffs = diff(&active, &reference);
+
+        assert_that!(diffs
+            .iter()
+            .all(|diff| diff.from.dimension() == Unit::Bar.dimension()))
+        .is_true();
+        assert_that!(diffs).contains(RuleDiff {
+            from: Unit::Bar,
+            to: Unit::Pascal,
+            factor: 100001.0,
+            reference_factor: 100000.0,
+            offset: 0.0,
+            reference_offset: 0.0,
+            relative_difference: 0.00001,
+        });
+        assert_that!(diff(&reference, &reference)).is_empty();
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn should_report_added_rules_in_diff() {
+        let client = admin_client(rocket::Config::figment());
+        let diffs = || -> Vec<RuleDiff> {
+            let response = client.get("/rules/diff").dispatch();
+            assert_that!(response.status()).is_equal_to(Status::Ok);
+            response.into_json().expect("valid json body")
+        };
+
+        assert_that!(diffs()).is_empty();
+        add_rule(&client, json!({"from": "kilo", "to": "lb", "factor": 2.2}));
+
+        let diffs = diffs();
+        assert_that!(diffs
+            .iter()
+            .any(|diff| (diff.from, diff.to) == (Unit::Kilo, Unit::Lb)))
+        .is_true();
+        assert_that!(diffs
+            .windows(2)
+            .all(|pair| (pair[0].from, pair[0].to) < (pair[1].from, pair[1].to)))
+        .is_true();
+    }
+
+    #[test]
+    #[cfg(feature = "admin")]
+    fn exported_rules_should_restore_the_same_table() {
+        let client = admin_client(rocket::Config::figment());
+        let response = add_rule(
+            &client,
+            json!({"from": "stone", "to": "kilo", "factor": 6.5}),
+        );
+        assert_that!(response.status()).is_equal_to(Status::Created);
+
+        let export = client
+            .get("/rules/export")
+            .dispatch()
+            .into_string()
+            .expect("exported rules");
+        let path =
+            std::env::temp_dir().join(format!("conversion_api_{}_export.json", std::process::id()));
+        std::fs::write(&path, export).expect("writable overrides file");
+        let figment = rocket::Config::figment().merge(("overrides_file", &path));
+        let restored =
+            Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let convert = |client: &Client, from, to| {
+            client
+                .post("/convert?debug=true")
+                .json(&ConversionRequest::new(from, to, 3.0))
+                .dispatch()
+                .into_json::<ConversionResponse>()
+        };
+        for from in Unit::ALL {
+            for to in Unit::ALL
+                .into_iter()
+                .filter(|to| to.dimension() == from.dimension())
+            {
+                let description = format!("{from:?} to {to:?}");
+                asserting!(&description)
+                    .that(&convert(&restored, from, to))
+                    .is_equal_to(convert(&client, from, to));
+            }
+        }
+        assert_that!(convert(&restored, Unit::Stone, Unit::Kilo).map(|response| response.result))
+            .contains(ConversionResult::Number(19.5));
+    }
+}
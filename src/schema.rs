@@ -0,0 +1,87 @@
+use crate::Unit;
+use rocket::serde::json::serde_json::json;
+use rocket::serde::json::{serde_json, Value};
+use std::collections::BTreeSet;
+
+/// The JSON Schema (draft 2020-12) of a `POST /convert` body, so clients can validate their
+/// requests before sending them.
+///
+/// Units are a string enum of the unit names and symbols, or a unit name qualified by its
+/// system. Configured aliases are accepted by the API but are not part of the schema.
+pub fn conversion_request() -> Value {
+    let units: BTreeSet<String> = Unit::ALL
+        .iter()
+        .filter_map(|unit| serde_json::to_value(unit).ok())
+        .filter_map(|name| name.as_str().map(str::to_string))
+        .chain(Unit::SYMBOLS.iter().map(|symbol| symbol.to_string()))
+        .collect();
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "ConversionRequest",
+        "type": "object",
+        "required": ["from", "to", "quantity"],
+        "properties": {
+            "from": { "$ref": "#/$defs/unit" },
+            "to": { "$ref": "#/$defs/unit" },
+            "through": { "$ref": "#/$defs/unit" },
+            "quantity": {
+                "oneOf": [
+                    { "type": "number" },
+                    { "type": "string", "pattern": r"^\s*[+-]?(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?\s*$" }
+                ]
+            }
+        },
+        "$defs": {
+            "unit": {
+                "oneOf": [
+                    { "type": "string", "enum": units },
+                    {
+                        "type": "object",
+                        "required": ["unit", "system"],
+                        "properties": {
+                            "unit": { "type": "string" },
+                            "system": { "enum": ["metric", "short", "long"] }
+                        }
+                    }
+                ]
+            }
+        }
+    })
+}
+
+/// The JSON Schema of the `POST /convert` request body.
+#[get("/schema/request")]
+pub fn request() -> Value {
+    conversion_request()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rocket;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_describe_units_as_a_string_enum() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/schema/request").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        assert_that!(response.content_type()).contains(ContentType::JSON);
+        let schema: Value = response.into_json().expect("valid json body");
+        let units: Vec<&str> = schema["$defs"]["unit"]["oneOf"][0]["enum"]
+            .as_array()
+            .expect("unit enum")
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        assert_that!(units).contains("kilo");
+        assert_that!(units).contains("long_ton");
+        assert_that!(units).contains("lb");
+        assert_that!(schema["required"]).is_equal_to(Value::from(vec!["from", "to", "quantity"]));
+    }
+}
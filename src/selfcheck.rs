@@ -0,0 +1,125 @@
+use crate::converter::Converter;
+use crate::Unit;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+
+/// Reference conversion factors, with the absolute tolerance allowed for each of them.
+const REFERENCE_FACTORS: [(Unit, Unit, f64, f64); 35] = [
+    (Unit::Lb, Unit::Gram, 453.59237, 0.00001),
+    (Unit::Lb, Unit::Kilo, 0.45359237, 0.00001),
+    (Unit::Lb, Unit::Ton, 0.00045359, 0.00000001),
+    (Unit::Lb, Unit::Microgram, 453_592_370.0, 0.00001),
+    (Unit::Kilo, Unit::Lb, 2.20462262, 0.00001),
+    (Unit::Kilo, Unit::Gram, 1000.0, 0.00001),
+    (Unit::Kilo, Unit::Ton, 0.001, 0.00001),
+    (Unit::Gram, Unit::Lb, 0.00220462, 0.00001),
+    (Unit::Gram, Unit::Kilo, 0.001, 0.00001),
+    (Unit::Gram, Unit::Ton, 0.000001, 0.00000001),
+    (Unit::Ton, Unit::Lb, 2204.62262185, 0.00001),
+    (Unit::Ton, Unit::Kilo, 1000.0, 0.00001),
+    (Unit::Ton, Unit::Gram, 1_000_000.0, 0.00001),
+    (Unit::Milligram, Unit::Gram, 0.001, 0.00001),
+    (Unit::ShortTon, Unit::Kilo, 907.18474, 0.00001),
+    (Unit::LongTon, Unit::Lb, 2240.0, 0.00001),
+    (Unit::Stone, Unit::Lb, 14.0, 0.00001),
+    (Unit::Ounce, Unit::Gram, 28.349523125, 0.00001),
+    (Unit::Mole, Unit::Count, 6.02214076e23, 1e8),
+    (Unit::Celsius, Unit::Fahrenheit, 1.8, 0.00000001),
+    (Unit::PsiGauge, Unit::Pascal, 6894.757, 0.00001),
+    (Unit::Kilohertz, Unit::Hertz, 1000.0, 0.00001),
+    (Unit::Rpm, Unit::Hertz, 0.01666667, 0.00000001),
+    (Unit::Minute, Unit::Second, 60.0, 0.00001),
+    (Unit::Hour, Unit::Minute, 60.0, 0.00001),
+    (
+        Unit::Degree,
+        Unit::Radian,
+        std::f64::consts::PI / 180.0,
+        0.00000001,
+    ),
+    (Unit::Degree, Unit::Gradian, 10.0 / 9.0, 0.00001),
+    (
+        Unit::Radian,
+        Unit::Gradian,
+        200.0 / std::f64::consts::PI,
+        0.00001,
+    ),
+    (Unit::Gradian, Unit::Degree, 0.9, 0.00001),
+    (Unit::Bar, Unit::Pascal, 100_000.0, 0.00001),
+    (Unit::Psi, Unit::Pascal, 6894.757, 0.00001),
+    (Unit::Bar, Unit::Psi, 14.5037738, 0.00001),
+    (Unit::Mpg, Unit::LPer100Km, 235.2145833, 0.00001),
+    (Unit::Calorie, Unit::Joule, 4.184, 0.00001),
+    (Unit::KilowattHour, Unit::Joule, 3_600_000.0, 0.00001),
+];
+
+/// The outcome of the self check.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct SelfCheckReport {
+    /// `true` when every factor matches its reference value.
+    pub passed: bool,
+    pub failures: Vec<FactorDeviation>,
+}
+
+/// A conversion factor deviating from its reference value.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct FactorDeviation {
+    pub from: Unit,
+    pub to: Unit,
+    pub expected: f64,
+    /// The factor found in the conversion table, `None` if there is no such rule.
+    pub actual: Option<f64>,
+    pub tolerance: f64,
+}
+
+/// Compare the conversion table factors against their reference values.
+#[get("/selfcheck")]
+pub fn selfcheck(converter: Converter) -> Json<SelfCheckReport> {
+    let failures: Vec<FactorDeviation> = REFERENCE_FACTORS
+        .into_iter()
+        .filter_map(|(from, to, expected, tolerance)| {
+            let actual = converter.table.find(from, to).map(|rule| rule.factor());
+
+            match actual {
+                Some(actual) if (actual - expected).abs() <= tolerance => None,
+                actual => Some(FactorDeviation {
+                    from,
+                    to,
+                    expected,
+                    actual,
+                    tolerance,
+                }),
+            }
+        })
+        .collect();
+
+    Json(SelfCheckReport {
+        passed: failures.is_empty(),
+        failures,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rocket;
+    use crate::selfcheck::SelfCheckReport;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn selfcheck_should_pass() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/selfcheck").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        assert_that!(response.into_json::<SelfCheckReport>())
+            .is_some()
+            .is_equal_to(SelfCheckReport {
+                passed: true,
+                failures: vec![],
+            });
+    }
+}
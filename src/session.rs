@@ -0,0 +1,284 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::format::OutputOptions;
+use crate::{numeric, system, ConversionRequest, ConversionResponse, Unit};
+use rocket::fairing::AdHoc;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::State;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long, in seconds, a session is kept when no `session_ttl` is configured.
+const DEFAULT_TTL: u64 = 60 * 60;
+
+/// How many sessions can be held at once when no `max_sessions` is configured.
+const DEFAULT_MAX_SESSIONS: usize = 10_000;
+
+/// Manage a [`SessionStore`] whose sessions expire after the configured `session_ttl`, holding
+/// at most `max_sessions` sessions.
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Session store", |rocket| async {
+        let ttl = rocket
+            .figment()
+            .extract_inner("session_ttl")
+            .unwrap_or(DEFAULT_TTL);
+        let max_sessions = rocket
+            .figment()
+            .extract_inner("max_sessions")
+            .unwrap_or(DEFAULT_MAX_SESSIONS);
+
+        rocket.manage(SessionStore::new(Duration::from_secs(ttl), max_sessions))
+    })
+}
+
+/// The units applied to the conversions of a session when they do not specify them.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionDefaults {
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    from: Unit,
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    to: Unit,
+    #[serde(
+        default,
+        deserialize_with = "system::optional_unit_or_qualified",
+        skip_serializing_if = "Option::is_none"
+    )]
+    through: Option<Unit>,
+}
+
+/// A created session, conversions are posted to `/sessions/<id>/convert`.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct Session {
+    pub id: String,
+}
+
+/// A conversion request whose units, when missing, are taken from the session defaults.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionConversionRequest {
+    #[serde(default, deserialize_with = "system::optional_unit_or_qualified")]
+    from: Option<Unit>,
+    #[serde(default, deserialize_with = "system::optional_unit_or_qualified")]
+    to: Option<Unit>,
+    #[serde(default, deserialize_with = "system::optional_unit_or_qualified")]
+    through: Option<Unit>,
+    #[serde(deserialize_with = "numeric::number_or_string")]
+    quantity: f64,
+}
+
+impl SessionConversionRequest {
+    fn merge(&self, defaults: &SessionDefaults) -> ConversionRequest {
+        ConversionRequest {
+            from: self.from.unwrap_or(defaults.from),
+            to: self.to.unwrap_or(defaults.to),
+            quantity: self.quantity,
+            through: self.through.or(defaults.through),
+        }
+    }
+}
+
+/// The session defaults keyed by their session id, along with the ids in creation order, so
+/// that expired sessions are found oldest first without going through every session.
+#[derive(Default)]
+struct Sessions {
+    defaults: HashMap<String, (Instant, SessionDefaults)>,
+    created: VecDeque<(Instant, String)>,
+}
+
+impl Sessions {
+    /// Remove the sessions expired at `now`, only visiting the expired ones.
+    fn remove_expired(&mut self, now: Instant, ttl: Duration) {
+        while let Some((created_at, id)) = self.created.front() {
+            if now.duration_since(*created_at) < ttl {
+                break;
+            }
+            self.defaults.remove(id);
+            self.created.pop_front();
+        }
+    }
+}
+
+/// Session defaults, keyed by their session id.
+pub struct SessionStore {
+    ttl: Duration,
+    max_sessions: usize,
+    next_id: AtomicU64,
+    hasher: RandomState,
+    sessions: Mutex<Sessions>,
+}
+
+impl SessionStore {
+    pub fn new(ttl: Duration, max_sessions: usize) -> Self {
+        SessionStore {
+            ttl,
+            max_sessions,
+            next_id: AtomicU64::new(0),
+            hasher: RandomState::new(),
+            sessions: Mutex::default(),
+        }
+    }
+
+    /// Store the defaults of a new session, returning its id. Refused while `max_sessions`
+    /// sessions have not expired yet.
+    pub fn create(&self, defaults: SessionDefaults) -> Result<String, ConvertError<'static>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        sessions.remove_expired(now, self.ttl);
+        if sessions.defaults.len() >= self.max_sessions {
+            return Err(ConvertError::TooManySessions {
+                max: self.max_sessions,
+            });
+        }
+
+        // Hashing a counter with a random key gives unique ids that cannot be guessed in sequence
+        let id = format!(
+            "{:016x}",
+            self.hasher
+                .hash_one(self.next_id.fetch_add(1, Ordering::Relaxed))
+        );
+        sessions.defaults.insert(id.clone(), (now, defaults));
+        sessions.created.push_back((now, id.clone()));
+        Ok(id)
+    }
+
+    /// The defaults of the session, `None` if it does not exist or has expired.
+    pub fn get(&self, id: &str) -> Option<SessionDefaults> {
+        let sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        sessions
+            .defaults
+            .get(id)
+            .filter(|(created_at, _)| created_at.elapsed() < self.ttl)
+            .map(|(_, defaults)| *defaults)
+    }
+}
+
+/// Create a session holding the units applied to its conversions by default.
+#[post("/sessions", data = "<defaults>")]
+pub fn create(
+    defaults: Json<SessionDefaults>,
+    store: &State<SessionStore>,
+) -> Result<(Status, Json<Session>), ConvertError<'static>> {
+    let id = store.create(defaults.into_inner())?;
+    Ok((Status::Created, Json(Session { id })))
+}
+
+/// Convert the requested quantity, units missing from the request are taken from the session
+/// defaults. The query string controls how the result is displayed (see [`OutputOptions`]).
+#[post("/sessions/<id>/convert?<options..>", data = "<conversion>")]
+pub fn convert(
+    id: &str,
+    conversion: Json<SessionConversionRequest>,
+    options: OutputOptions,
+    store: &State<SessionStore>,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<ConversionResponse>, ConvertError<'static>> {
+    let defaults = store
+        .get(id)
+        .ok_or_else(|| ConvertError::UnknownSession(id.to_string()))?;
+
+    let response =
+        ConversionResponse::with_options(&conversion.merge(&defaults), None, &options, &converter)?;
+    quotas.consume(&api_key, 1)?;
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ErrorBody;
+    use crate::rocket;
+    use crate::session::{Session, SessionDefaults, SessionStore};
+    use crate::Unit;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+    use std::time::Duration;
+
+    #[test]
+    fn should_apply_session_defaults() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/sessions")
+            .json(&json!({"from": "kilo", "to": "gram"}))
+            .dispatch();
+        assert_that!(response.status()).is_equal_to(Status::Created);
+        let session: Session = response.into_json().expect("valid json body");
+
+        let convert = |body| {
+            let response = client
+                .post(format!("/sessions/{}/convert", session.id))
+                .json(&body)
+                .dispatch();
+            assert_that!(response.status()).is_equal_to(Status::Ok);
+            let body: Value = response.into_json().expect("valid json body");
+            body["result"].as_f64()
+        };
+
+        assert_that!(convert(json!({"quantity": 5}))).contains(5000.0);
+        assert_that!(convert(json!({"to": "milligram", "quantity": 5}))).contains(5_000_000.0);
+    }
+
+    #[test]
+    fn unknown_session_should_not_be_found() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/sessions/unknown/convert")
+            .json(&json!({"quantity": 5}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::NotFound);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error).contains("unknown");
+    }
+
+    #[test]
+    fn should_refuse_sessions_beyond_the_limit() {
+        let figment = rocket::Config::figment().merge(("max_sessions", 2));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+        let create = || {
+            client
+                .post("/sessions")
+                .json(&json!({"from": "kilo", "to": "gram"}))
+                .dispatch()
+        };
+
+        assert_that!(create().status()).is_equal_to(Status::Created);
+        assert_that!(create().status()).is_equal_to(Status::Created);
+        let response = create();
+        assert_that!(response.status()).is_equal_to(Status::ServiceUnavailable);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.retryable).is_true();
+    }
+
+    #[test]
+    fn should_make_room_for_sessions_once_others_expired() {
+        let store = SessionStore::new(Duration::ZERO, 1);
+        let defaults = SessionDefaults {
+            from: Unit::Kilo,
+            to: Unit::Gram,
+            through: None,
+        };
+
+        let first = store.create(defaults).expect("room for a session");
+        assert_that!(store.get(&first)).is_none();
+        assert_that!(store.create(defaults)).is_ok();
+
+        let sessions = store.sessions.lock().expect("unpoisoned lock");
+        assert_that!(sessions.defaults).has_length(1);
+        assert_that!(sessions.created.len()).is_equal_to(1);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::converter::Converter;
+use crate::{ConversionRequest, ConversionResponse};
+use rocket::data::{ByteUnit, Data, Limits};
+use rocket::http::ContentType;
+use rocket::response::stream::TextStream;
+use rocket::serde::json::serde_json;
+use rocket::tokio::io::AsyncReadExt;
+use rocket::tokio::task;
+use rocket::State;
+
+/// Size of the chunks read from the request body.
+const CHUNK_SIZE: usize = 4096;
+
+/// Body size limit used when no `stream` limit is configured.
+const DEFAULT_STREAM_LIMIT: ByteUnit = ByteUnit::GiB;
+
+/// Convert a JSON array of [`ConversionRequest`] element by element.
+///
+/// The body is never buffered as a whole: each element is parsed as soon as it is complete
+/// and its [`ConversionResponse`] is written to the response stream right away. The elements
+/// are converted on the blocking thread pool, so a large stream does not starve the async runtime.
+/// If an element cannot be processed, or the body ends before the array is closed,
+/// an `{"error": "..."}` entry is written and the stream ends.
+#[post("/convert/stream", data = "<data>")]
+pub fn convert_stream<'r>(
+    data: Data<'r>,
+    limits: &Limits,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &'r State<Quotas>,
+) -> (ContentType, TextStream![String + 'r]) {
+    let limit = limits.get("stream").unwrap_or(DEFAULT_STREAM_LIMIT);
+    let mut body = data.open(limit);
+
+    let stream = TextStream! {
+        let mut splitter = ArraySplitter::default();
+        let mut chunk = [0; CHUNK_SIZE];
+        let mut separator = "";
+
+        yield "[".to_string();
+        'read: loop {
+            let read = match body.read(&mut chunk).await {
+                Ok(0) => {
+                    if let Err(err) = splitter.finish() {
+                        yield format!("{separator}{}", error_entry(err));
+                    }
+                    break 'read;
+                }
+                Ok(read) => read,
+                Err(err) => {
+                    yield format!("{separator}{}", error_entry(&err.to_string()));
+                    break 'read;
+                }
+            };
+
+            match splitter.feed(&chunk[..read]) {
+                Ok(elements) => {
+                    for result in convert_elements(elements, converter.clone()).await {
+                        let charged = result.and_then(|response| {
+                            quotas
+                                .consume(&api_key, 1)
+                                .map(|()| response)
+                                .map_err(|err| error_entry(&err.to_string()))
+                        });
+                        match charged {
+                            Ok(response) => yield format!("{separator}{response}"),
+                            Err(err) => {
+                                yield format!("{separator}{err}");
+                                break 'read;
+                            }
+                        }
+                        separator = ",";
+                    }
+                }
+                Err(err) => {
+                    yield format!("{separator}{}", error_entry(err));
+                    break 'read;
+                }
+            }
+        }
+        yield "]".to_string();
+    };
+
+    (ContentType::JSON, stream)
+}
+
+/// Convert the elements completed by a chunk on the blocking thread pool, in order.
+async fn convert_elements(
+    elements: Vec<Vec<u8>>,
+    converter: Converter,
+) -> Vec<Result<String, String>> {
+    if elements.is_empty() {
+        return vec![];
+    }
+
+    task::spawn_blocking(move || {
+        elements
+            .iter()
+            .map(|element| convert_element(element, &converter))
+            .collect()
+    })
+    .await
+    .unwrap_or_else(|err| vec![Err(error_entry(&err.to_string()))])
+}
+
+fn convert_element(element: &[u8], converter: &Converter) -> Result<String, String> {
+    let request: ConversionRequest =
+        serde_json::from_slice(element).map_err(|err| error_entry(&err.to_string()))?;
+
+    let response = ConversionResponse::new(&request, converter)
+        .map_err(|err| error_entry(&err.to_string()))?;
+
+    serde_json::to_string(&response).map_err(|err| error_entry(&err.to_string()))
+}
+
+fn error_entry(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum SplitterState {
+    /// Waiting for the opening bracket of the array.
+    #[default]
+    Start,
+    /// Inside the array, between or within elements.
+    Array,
+    /// The closing bracket has been reached.
+    End,
+}
+
+/// Incrementally split the bytes of a JSON array into its top level elements.
+///
+/// Only the bytes of the element currently being read are kept in memory.
+#[derive(Debug, Default)]
+struct ArraySplitter {
+    state: SplitterState,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    element: Vec<u8>,
+}
+
+impl ArraySplitter {
+    /// Feed the next chunk of input, returning the elements completed by this chunk.
+    fn feed(&mut self, bytes: &[u8]) -> Result<Vec<Vec<u8>>, &'static str> {
+        let mut elements = vec![];
+
+        for &byte in bytes {
+            match self.state {
+                SplitterState::Start if byte.is_ascii_whitespace() => {}
+                SplitterState::Start if byte == b'[' => self.state = SplitterState::Array,
+                SplitterState::Start => return Err("expected a JSON array"),
+                SplitterState::End if byte.is_ascii_whitespace() => {}
+                SplitterState::End => return Err("unexpected data after the end of the array"),
+                SplitterState::Array => {
+                    if let Some(element) = self.push(byte) {
+                        elements.push(element);
+                    }
+                }
+            }
+        }
+
+        Ok(elements)
+    }
+
+    /// Check that the input ended with the closing bracket of the array. Otherwise the last
+    /// element may be truncated, and is never returned.
+    fn finish(&self) -> Result<(), &'static str> {
+        match self.state {
+            SplitterState::Start => Err("expected a JSON array"),
+            SplitterState::Array => Err("the body ended before the end of the array"),
+            SplitterState::End => Ok(()),
+        }
+    }
+
+    fn push(&mut self, byte: u8) -> Option<Vec<u8>> {
+        if self.in_string {
+            match byte {
+                _ if self.escaped => self.escaped = false,
+                b'\\' => self.escaped = true,
+                b'"' => self.in_string = false,
+                _ => {}
+            }
+            self.element.push(byte);
+            return None;
+        }
+
+        match byte {
+            b',' | b']' if self.depth == 0 => {
+                if byte == b']' {
+                    self.state = SplitterState::End;
+                }
+
+                let element = std::mem::take(&mut self.element);
+                if element.iter().all(u8::is_ascii_whitespace) {
+                    None
+                } else {
+                    Some(element)
+                }
+            }
+            _ => {
+                match byte {
+                    b'"' => self.in_string = true,
+                    b'{' | b'[' => self.depth += 1,
+                    b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+                    _ => {}
+                }
+                self.element.push(byte);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArraySplitter;
+    use crate::{rocket, ConversionResponse, ConversionResult};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_split_elements_across_chunks() {
+        let mut splitter = ArraySplitter::default();
+        let input = br#" [{"a": "]"}, {"b": [1, 2]} ,{"c": "\"}"}] "#;
+
+        let elements: Vec<Vec<u8>> = input
+            .chunks(3)
+            .flat_map(|chunk| splitter.feed(chunk).expect("valid array"))
+            .collect();
+
+        assert_that!(elements).is_equal_to(vec![
+            br#"{"a": "]"}"#.to_vec(),
+            br#" {"b": [1, 2]} "#.to_vec(),
+            br#"{"c": "\"}"}"#.to_vec(),
+        ]);
+    }
+
+    #[test]
+    fn should_reject_non_array_input() {
+        let mut splitter = ArraySplitter::default();
+        assert_that!(splitter.feed(b"{}")).is_err();
+    }
+
+    #[test]
+    fn should_reject_unclosed_array() {
+        let mut splitter = ArraySplitter::default();
+        assert_that!(splitter.finish()).is_err();
+
+        splitter
+            .feed(br#"[{"a": 1}, {"b""#)
+            .expect("valid array start");
+        assert_that!(splitter.finish()).is_err();
+
+        splitter.feed(br#": 2}]"#).expect("valid array end");
+        assert_that!(splitter.finish()).is_ok();
+    }
+
+    fn stream(body: &str) -> Vec<Value> {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let response = client
+            .post("/convert/stream")
+            .header(ContentType::JSON)
+            .body(body)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        response.into_json().expect("valid json array")
+    }
+
+    #[test]
+    fn should_end_truncated_body_with_an_error() {
+        let results = stream(
+            r#"[{"from":"gram","to":"kilo","quantity":1000},{"from":"gram","to":"kilo","quan"#,
+        );
+
+        assert_that!(results).has_length(2);
+        assert_that!(results[0]["result"].as_f64()).contains(1.0);
+        assert_that!(results[1]["error"].as_str())
+            .contains("the body ended before the end of the array");
+    }
+
+    #[test]
+    fn should_answer_empty_body_with_an_error() {
+        let results = stream("");
+
+        assert_that!(results).has_length(1);
+        assert_that!(results[0]["error"].as_str()).contains("expected a JSON array");
+    }
+
+    #[test]
+    fn should_stream_large_array() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let count = 20_000;
+        let element = r#"{"from":"gram","to":"kilo","quantity":1000}"#;
+        let body = format!("[{}]", vec![element; count].join(","));
+
+        let response = client
+            .post("/convert/stream")
+            .header(ContentType::JSON)
+            .body(body)
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let results: Vec<ConversionResponse> = response.into_json().expect("valid json array");
+        assert_that!(results).has_length(count);
+        assert_that!(results
+            .iter()
+            .all(|response| response.result == ConversionResult::Number(1.0)))
+        .is_true();
+    }
+}
@@ -0,0 +1,114 @@
+use crate::converter::{Converter, SharedConverter};
+use crate::{Dimension, Unit};
+use rocket::fairing::AdHoc;
+use std::fmt;
+
+/// Log a [`StartupSummary`] of the converter built by `converter::stage`, which must be
+/// attached first.
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Startup summary", |rocket| async {
+        if let Some(converter) = rocket.state::<SharedConverter>() {
+            info!("{}", StartupSummary::new(&converter.current()));
+        }
+        rocket
+    })
+}
+
+/// What the converter was built with, so a misconfiguration shows in the startup logs.
+#[derive(Debug, PartialEq)]
+pub struct StartupSummary {
+    pub units: usize,
+    pub dimensions: usize,
+    pub rules: usize,
+    /// The dimensions having at least one conversion that is not disabled.
+    pub enabled_dimensions: Vec<Dimension>,
+}
+
+impl StartupSummary {
+    pub fn new(converter: &Converter) -> Self {
+        let enabled_dimensions = Dimension::ALL
+            .into_iter()
+            .filter(|dimension| {
+                let units = || {
+                    Unit::ALL
+                        .into_iter()
+                        .filter(|unit| unit.dimension() == *dimension)
+                };
+                units().any(|from| {
+                    units().any(|to| from != to && !converter.config.is_disabled(from, to))
+                })
+            })
+            .collect();
+
+        StartupSummary {
+            units: Unit::ALL.len(),
+            dimensions: Dimension::ALL.len(),
+            rules: converter.table.len(),
+            enabled_dimensions,
+        }
+    }
+}
+
+impl fmt::Display for StartupSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let enabled: Vec<String> = self
+            .enabled_dimensions
+            .iter()
+            .map(|dimension| format!("{dimension:?}").to_lowercase())
+            .collect();
+
+        write!(
+            f,
+            "Loaded {} units, {} dimensions and {} conversion rules, enabled dimensions: {}",
+            self.units,
+            self.dimensions,
+            self.rules,
+            enabled.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::{Config, UnitPair};
+    use crate::converter::Converter;
+    use crate::summary::StartupSummary;
+    use crate::{Dimension, Unit};
+    use speculoos::prelude::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn should_summarize_loaded_converter() {
+        let summary = StartupSummary::new(&Converter::default());
+
+        assert_that!(summary.units).is_equal_to(Unit::ALL.len());
+        assert_that!(summary.enabled_dimensions).is_equal_to(Dimension::ALL.to_vec());
+        assert_that!(summary.to_string()).starts_with(format!(
+            "Loaded {} units, {} dimensions and {} conversion rules",
+            Unit::ALL.len(),
+            Dimension::ALL.len(),
+            summary.rules
+        ));
+    }
+
+    #[test]
+    fn should_leave_out_fully_disabled_dimensions() {
+        let disable = |from, to| UnitPair { from, to };
+        let converter = Converter {
+            config: Arc::new(Config {
+                disabled_conversions: vec![
+                    disable(Unit::Mole, Unit::Count),
+                    disable(Unit::Count, Unit::Mole),
+                ],
+                ..Config::default()
+            }),
+            ..Converter::default()
+        };
+
+        let summary = StartupSummary::new(&converter);
+
+        assert_that!(summary.enabled_dimensions).does_not_contain(Dimension::Substance);
+        assert_that!(summary.to_string()).contains("enabled dimensions: mass, angle");
+        assert_that!(summary.to_string()).does_not_contain("substance");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::api_key::{ApiKey, Quotas};
+use crate::array;
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::format;
+use crate::{system, Unit};
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::State;
+
+/// Convert the quantities from `start` to `end`, both included, every `step`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct SweepConversionRequest {
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub from: Unit,
+    #[serde(deserialize_with = "system::unit_or_qualified")]
+    pub to: Unit,
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+}
+
+/// The swept quantities and their converted value, in increasing order.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct SweepConversion {
+    #[serde(serialize_with = "format::serialize_numbers")]
+    pub quantities: Vec<f64>,
+    #[serde(serialize_with = "format::serialize_numbers")]
+    pub results: Vec<f64>,
+}
+
+impl SweepConversionRequest {
+    /// The swept quantities, at most `max` of them.
+    fn quantities(&self, max: usize) -> Result<Vec<f64>, ConvertError<'static>> {
+        let invalid = |reason: String| Err(ConvertError::InvalidSweep(reason));
+        if !(self.start.is_finite() && self.end.is_finite()) {
+            return invalid("start and end must be finite".to_string());
+        }
+        if !(self.step.is_finite() && self.step > 0.0) {
+            return invalid(format!("step must be a positive number, got {}", self.step));
+        }
+        if self.end < self.start {
+            return invalid(format!(
+                "end {} is lower than start {}",
+                self.end, self.start
+            ));
+        }
+
+        // Count the points as a float, before allocating them: a tiny step over a large range
+        // gives more points than any integer can hold. Tolerate the rounding error of the
+        // division so that `end` is included when reached.
+        let points = ((self.end - self.start) / self.step + 1e-9).floor() + 1.0;
+        if points > max as f64 {
+            return Err(ConvertError::TooManyPoints { max, got: points });
+        }
+
+        Ok((0..points as usize)
+            .map(|i| self.start + i as f64 * self.step)
+            .collect())
+    }
+}
+
+/// Convert every quantity of the sweep with the same conversion rule, like `POST /convert/array`.
+/// Sweeps of more than the configured `max_sweep_points` points are rejected.
+#[post("/convert/sweep", data = "<conversion>")]
+pub fn convert_sweep(
+    conversion: Json<SweepConversionRequest>,
+    converter: Converter,
+    api_key: ApiKey,
+    quotas: &State<Quotas>,
+) -> Result<Json<SweepConversion>, ConvertError<'static>> {
+    let quantities = conversion.quantities(converter.config.max_sweep_points)?;
+    let results = array::convert_quantities(
+        conversion.from,
+        conversion.to,
+        quantities.clone(),
+        &converter,
+    )?;
+    quotas.consume(&api_key, results.len() as u64)?;
+
+    Ok(Json(SweepConversion {
+        quantities,
+        results,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ErrorBody;
+    use crate::rocket;
+    use crate::sweep::SweepConversion;
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::serde_json::json;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_convert_every_point() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/sweep")
+            .json(&json!({"from": "kg", "to": "lb", "start": 0, "end": 2, "step": 0.5}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let sweep: SweepConversion = response.into_json().expect("valid json body");
+        assert_that!(sweep.quantities).is_equal_to(vec![0.0, 0.5, 1.0, 1.5, 2.0]);
+        assert_that!(sweep.results)
+            .is_equal_to(vec![0.0, 1.10231131, 2.20462262, 3.30693393, 4.40924524]);
+    }
+
+    #[test]
+    fn should_refuse_invalid_sweeps() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+        let sweep = |start: f64, end: f64, step: f64| {
+            client
+                .post("/convert/sweep")
+                .json(&json!({"from": "kg", "to": "lb", "start": start, "end": end, "step": step}))
+                .dispatch()
+                .status()
+        };
+
+        assert_that!(sweep(0.0, 10.0, 0.0)).is_equal_to(Status::BadRequest);
+        assert_that!(sweep(0.0, 10.0, -1.0)).is_equal_to(Status::BadRequest);
+        assert_that!(sweep(10.0, 0.0, 1.0)).is_equal_to(Status::BadRequest);
+        assert_that!(sweep(0.0, 1e6, 1.0)).is_equal_to(Status::BadRequest);
+    }
+
+    #[test]
+    fn should_refuse_huge_sweeps_before_allocating() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert/sweep")
+            .json(&json!({"from": "kg", "to": "lb", "start": 0, "end": 1e300, "step": 1e-300}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::BadRequest);
+        let body: ErrorBody = response.into_json().expect("valid json body");
+        assert_that!(body.error)
+            .is_equal_to("A sweep can hold at most 1000 points, got inf".to_string());
+    }
+}
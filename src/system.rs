@@ -0,0 +1,227 @@
+use crate::{Dimension, Unit};
+use rocket::serde::de::value::MapAccessDeserializer;
+use rocket::serde::de::{Error, IntoDeserializer, MapAccess, Visitor};
+use rocket::serde::{Deserialize, Deserializer};
+use std::fmt;
+
+/// A system of units, disambiguating unit names shared by several systems such as "ton".
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, FromFormField)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum System {
+    Metric,
+    /// United States customary units.
+    #[serde(alias = "us")]
+    #[field(value = "short")]
+    #[field(value = "us")]
+    Short,
+    /// British imperial units.
+    #[serde(alias = "imperial")]
+    #[field(value = "long")]
+    #[field(value = "imperial")]
+    Long,
+}
+
+impl System {
+    /// Whether the unit belongs to this system. Metric units are the SI units and the units
+    /// accepted alongside them, units of neither dimension belong to the other systems.
+    pub fn contains(&self, unit: Unit) -> bool {
+        match self {
+            System::Metric => matches!(
+                unit,
+                Unit::Microgram
+                    | Unit::Milligram
+                    | Unit::Gram
+                    | Unit::Kilo
+                    | Unit::Ton
+                    | Unit::Radian
+                    | Unit::Pascal
+                    | Unit::Bar
+                    | Unit::LPer100Km
+                    | Unit::Joule
+                    | Unit::KilowattHour
+                    | Unit::Mole
+                    | Unit::Count
+                    | Unit::Celsius
+                    | Unit::Kelvin
+                    | Unit::Hertz
+                    | Unit::Kilohertz
+                    | Unit::Second
+                    | Unit::Minute
+                    | Unit::Hour
+            ),
+            System::Short => matches!(
+                unit,
+                Unit::Lb
+                    | Unit::Ounce
+                    | Unit::ShortTon
+                    | Unit::Psi
+                    | Unit::PsiGauge
+                    | Unit::Mpg
+                    | Unit::Fahrenheit
+            ),
+            System::Long => matches!(
+                unit,
+                Unit::Lb | Unit::Ounce | Unit::Stone | Unit::LongTon | Unit::Psi | Unit::PsiGauge
+            ),
+        }
+    }
+
+    /// The unit results are expressed in when this system is preferred, `None` if the system
+    /// has no unit of this dimension.
+    pub fn preferred_unit(&self, dimension: Dimension) -> Option<Unit> {
+        match (self, dimension) {
+            (System::Metric, dimension) => Some(dimension.base_unit()),
+            (System::Short | System::Long, Dimension::Mass) => Some(Unit::Lb),
+            (System::Short | System::Long, Dimension::Pressure) => Some(Unit::Psi),
+            (System::Short, Dimension::FuelEconomy) => Some(Unit::Mpg),
+            (System::Short, Dimension::Temperature) => Some(Unit::Fahrenheit),
+            (System::Short | System::Long, _) => None,
+        }
+    }
+}
+
+/// A unit name qualified by its system, e.g. `{"unit": "ton", "system": "short"}`.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct QualifiedUnit {
+    unit: String,
+    system: System,
+}
+
+impl QualifiedUnit {
+    /// The unit designated by this name in the given system. Unambiguous names designate
+    /// the same unit whatever the system.
+    fn resolve<E: Error>(&self) -> Result<Unit, E> {
+        match (self.unit.as_str(), self.system) {
+            ("ton", System::Metric) => Ok(Unit::Ton),
+            ("ton", System::Short) => Ok(Unit::ShortTon),
+            ("ton", System::Long) => Ok(Unit::LongTon),
+            (unit, _) => Unit::deserialize(unit.into_deserializer()),
+        }
+    }
+}
+
+struct UnitVisitor;
+
+impl<'de> Visitor<'de> for UnitVisitor {
+    type Value = Unit;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a unit name, or a unit name and its system")
+    }
+
+    fn visit_str<E: Error>(self, unit: &str) -> Result<Unit, E> {
+        // Unit symbols are accepted as well as unit names
+        Unit::deserialize(unit.into_deserializer())
+            .or_else(|err| Unit::try_from(unit).map_err(|_| err))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Unit, A::Error> {
+        QualifiedUnit::deserialize(MapAccessDeserializer::new(map))?.resolve()
+    }
+}
+
+/// Deserialize a unit from either its name or a [`QualifiedUnit`] object.
+pub fn unit_or_qualified<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Unit, D::Error> {
+    deserializer.deserialize_any(UnitVisitor)
+}
+
+/// Deserialize an optional unit from either its name or a [`QualifiedUnit`] object.
+pub fn optional_unit_or_qualified<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Unit>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(crate = "rocket::serde")]
+    struct Wrapper(#[serde(deserialize_with = "unit_or_qualified")] Unit);
+
+    Option::<Wrapper>::deserialize(deserializer).map(|unit| unit.map(|Wrapper(unit)| unit))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{rocket, ConversionRequest, ConversionResponse, ConversionResult, Unit};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::{serde_json, serde_json::json};
+    use speculoos::prelude::*;
+
+    fn units(json: serde_json::Value) -> Result<(Unit, Unit, Option<Unit>), serde_json::Error> {
+        serde_json::from_value::<ConversionRequest>(json)
+            .map(|request| (request.from, request.to, request.through))
+    }
+
+    #[test]
+    fn should_resolve_ton_by_system() {
+        let ton = |system| {
+            units(json!({"from": {"unit": "ton", "system": system}, "to": "kilo", "quantity": 1}))
+                .map(|(from, _, _)| from)
+        };
+
+        assert_that!(ton("metric")).is_ok().is_equal_to(Unit::Ton);
+        assert_that!(ton("short"))
+            .is_ok()
+            .is_equal_to(Unit::ShortTon);
+        assert_that!(ton("imperial"))
+            .is_ok()
+            .is_equal_to(Unit::LongTon);
+        assert_that!(ton("nautical")).is_err();
+    }
+
+    #[test]
+    fn should_accept_plain_and_qualified_units_together() {
+        let result = units(json!({
+            "from": "lb",
+            "to": {"unit": "kilo", "system": "metric"},
+            "through": {"unit": "ton", "system": "long"},
+            "quantity": 1
+        }));
+
+        assert_that!(result)
+            .is_ok()
+            .is_equal_to((Unit::Lb, Unit::Kilo, Some(Unit::LongTon)));
+    }
+
+    #[test]
+    fn should_return_metric_equivalent_of_customary_units() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?system=metric")
+            .json(&json!({"from": "lb", "to": "ounce", "quantity": 1}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let response: ConversionResponse = response.into_json().expect("valid json response");
+        assert_that!(response.result).is_equal_to(ConversionResult::Number(16.0));
+        assert_that!(response.preferred_unit).contains(Unit::Gram);
+        assert_that!(response.preferred_value).contains(453.59237);
+    }
+
+    #[test]
+    fn should_not_return_equivalent_of_units_of_the_preferred_system() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert?system=us")
+            .json(&json!({"from": "kilo", "to": "lb", "quantity": 1}))
+            .dispatch();
+
+        let response: ConversionResponse = response.into_json().expect("valid json response");
+        assert_that!(response.preferred_unit).is_none();
+        assert_that!(response.preferred_value).is_none();
+    }
+
+    #[test]
+    fn should_convert_short_ton_to_kilo() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client
+            .post("/convert")
+            .json(&json!({"from": {"unit": "ton", "system": "short"}, "to": "kilo", "quantity": 1}))
+            .dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let response: ConversionResponse = response.into_json().expect("valid json response");
+        assert_that!(response.result).is_equal_to(ConversionResult::Number(907.18474));
+    }
+}
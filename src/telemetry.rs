@@ -0,0 +1,176 @@
+use crate::ConversionRequest;
+use rocket::fairing::{AdHoc, Fairing, Info, Kind};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request, Response};
+use tracing::field::{debug, Empty};
+use tracing::{info_span, Span};
+
+/// Attach the [`Tracing`] fairing. When an `otlp_endpoint` is configured, and the `otel`
+/// feature enabled, spans are exported to it with the OTLP/HTTP protocol.
+///
+/// Without an exporter no subscriber is installed, spans are then disabled and cost next to nothing.
+pub fn stage() -> AdHoc {
+    AdHoc::on_ignite("Tracing", |rocket| async {
+        #[cfg(feature = "otel")]
+        let rocket = match rocket.figment().extract_inner::<String>("otlp_endpoint") {
+            Ok(endpoint) => match otlp::install(&endpoint) {
+                Ok(provider) => rocket.attach(AdHoc::on_shutdown("OTLP exporter", |_| {
+                    Box::pin(async move {
+                        if let Err(err) = provider.shutdown() {
+                            error!("Failed to flush spans: {}", err);
+                        }
+                    })
+                })),
+                Err(err) => {
+                    error!("Failed to install the OTLP exporter: {}", err);
+                    rocket
+                }
+            },
+            Err(_) => rocket,
+        };
+
+        rocket.attach(Tracing)
+    })
+}
+
+/// Wrap every `/convert` request in a `convert` span, ended once the request is dropped.
+pub struct Tracing;
+
+#[rocket::async_trait]
+impl Fairing for Tracing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request tracing",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        if request.uri().path() == "/convert" {
+            let span = info_span!(
+                target: "conversion_api",
+                "convert",
+                from = Empty,
+                to = Empty,
+                quantity = Empty,
+                http.status_code = Empty,
+            );
+            request.local_cache(|| RequestSpan(span));
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        request
+            .local_cache(RequestSpan::none)
+            .0
+            .record("http.status_code", i64::from(response.status().code));
+    }
+}
+
+/// The span of the current request, disabled for requests which are not traced.
+pub struct RequestSpan(Span);
+
+impl RequestSpan {
+    fn none() -> Self {
+        RequestSpan(Span::none())
+    }
+
+    /// Record the conversion handled by the request.
+    pub fn record(&self, request: &ConversionRequest) {
+        self.0
+            .record("from", debug(request.from))
+            .record("to", debug(request.to))
+            .record("quantity", request.quantity);
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r RequestSpan {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(request.local_cache(RequestSpan::none))
+    }
+}
+
+#[cfg(feature = "otel")]
+mod otlp {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::{ExporterBuildError, SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Install a subscriber exporting spans to `endpoint`, returning the provider
+    /// to flush on shutdown.
+    pub fn install(endpoint: &str) -> Result<SdkTracerProvider, ExporterBuildError> {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()?;
+        let provider = SdkTracerProvider::builder()
+            .with_resource(
+                Resource::builder()
+                    .with_service_name("conversion_api")
+                    .build(),
+            )
+            .with_batch_exporter(exporter)
+            .build();
+
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("conversion_api")));
+        if tracing::subscriber::set_global_default(subscriber).is_err() {
+            warn!("A tracing subscriber is already installed, spans will not be exported");
+        }
+
+        Ok(provider)
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod test {
+    use crate::rocket;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn should_emit_convert_span() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        tracing::subscriber::with_default(subscriber, || {
+            let response = client
+                .post("/convert")
+                .header(ContentType::JSON)
+                .body(r#"{"from":"kilo","to":"lb","quantity":2}"#)
+                .dispatch();
+            assert_that!(response.status()).is_equal_to(Status::Ok);
+        });
+
+        let spans = exporter.get_finished_spans().expect("finished spans");
+        let span = spans
+            .iter()
+            .find(|span| span.name == "convert")
+            .expect("convert span");
+        let attribute = |key: &str| {
+            span.attributes
+                .iter()
+                .find(|attribute| attribute.key.as_str() == key)
+                .map(|attribute| attribute.value.clone())
+        };
+        assert_that!(attribute("from")).contains(Value::from("Kilo"));
+        assert_that!(attribute("to")).contains(Value::from("Lb"));
+        assert_that!(attribute("quantity")).contains(Value::from(2.0));
+        assert_that!(attribute("http.status_code")).contains(Value::from(200));
+    }
+}
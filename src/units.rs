@@ -0,0 +1,80 @@
+use crate::converter::Converter;
+use crate::error::ConvertError;
+use crate::Unit;
+use rocket::serde::de::{value, IntoDeserializer};
+use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
+
+/// Parse a unit from a query parameter, given by its name or symbol.
+pub(crate) fn parse(name: &str) -> Result<Unit, ConvertError<'_>> {
+    Unit::deserialize(IntoDeserializer::<value::Error>::into_deserializer(name))
+        .or_else(|_| Unit::try_from(name))
+}
+
+/// List the units a quantity can be converted to from the given unit, designated by its name
+/// or symbol: every other unit of its dimension whose conversion is not disabled.
+#[get("/units/reachable?<from>")]
+pub fn reachable<'a>(
+    from: &'a str,
+    converter: Converter,
+) -> Result<Json<Vec<Unit>>, ConvertError<'a>> {
+    let from = parse(from)?;
+
+    Ok(Json(
+        Unit::ALL
+            .into_iter()
+            .filter(|to| *to != from && to.dimension() == from.dimension())
+            .filter(|to| !converter.config.is_disabled(from, *to))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::UnitPair;
+    use crate::{rocket, Unit};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_list_units_of_the_same_dimension() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/units/reachable?from=kg").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let units: Vec<Unit> = response.into_json().expect("valid json body");
+        assert_that!(units).contains(Unit::Lb);
+        assert_that!(units).contains(Unit::Gram);
+        assert_that!(units).contains(Unit::Ton);
+        assert_that!(units).does_not_contain(Unit::Kilo);
+        assert_that!(units).does_not_contain(Unit::Degree);
+    }
+
+    #[test]
+    fn should_leave_out_disabled_conversions() {
+        let disabled = vec![UnitPair {
+            from: Unit::Kilo,
+            to: Unit::Lb,
+        }];
+        let figment = rocket::Config::figment().merge(("disabled_conversions", disabled));
+        let client = Client::untracked(rocket().configure(figment)).expect("valid rocket instance");
+
+        let response = client.get("/units/reachable?from=kilo").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::Ok);
+        let units: Vec<Unit> = response.into_json().expect("valid json body");
+        assert_that!(units).does_not_contain(Unit::Lb);
+        assert_that!(units).contains(Unit::Gram);
+    }
+
+    #[test]
+    fn unknown_unit_should_be_refused() {
+        let client = Client::untracked(rocket()).expect("valid rocket instance");
+
+        let response = client.get("/units/reachable?from=parsec").dispatch();
+
+        assert_that!(response.status()).is_equal_to(Status::BadRequest);
+    }
+}